@@ -3,7 +3,7 @@
 name=smc
 output=packed/
 temp=temp/
-declare -a folders=("assets/" "config/")
+declare -a folders=("config/")
 
 # https://stackoverflow.com/a/33826763
 while [[ "$#" > 0 ]]; do case $1 in
@@ -33,6 +33,7 @@ if [ "$linux" == "1" ]; then
         cp -r $folder ${temp}/$folder
     done
     cp target/release/$name $temp
+    cargo run --release -- --build-pack ${temp}assets.pack
 
     cd $temp
     zip -r ${name}_linux.zip .
@@ -57,6 +58,7 @@ if [ "$windows" == "1" ]; then
         cp -r $folder ${temp}/$folder
     done
     cp target/x86_64-pc-windows-gnu/release/$name.exe $temp
+    cargo run --release -- --build-pack ${temp}assets.pack
     
     cd $temp
     zip -r ${name}_windows.zip .
@@ -0,0 +1,66 @@
+use super::AssetProgress;
+use crate::logger::prelude::*;
+use amethyst::{assets::Completion, prelude::*};
+
+// A set of assets a state loads while it keeps running, instead of blocking a loading screen
+pub struct BackgroundLoad<T> {
+    name: String,
+    progress: AssetProgress,
+    assets: T,
+    finished: bool,
+}
+
+impl<T> BackgroundLoad<T> {
+    pub fn start(
+        world: &mut World,
+        name: &str,
+        load: impl FnOnce(&mut World, &mut AssetProgress) -> T,
+    ) -> BackgroundLoad<T> {
+        info!("Loading {} in the background", name);
+
+        let mut progress = AssetProgress::new();
+        let assets = load(world, &mut progress);
+
+        BackgroundLoad {
+            name: name.to_string(),
+            progress,
+            assets,
+            finished: false,
+        }
+    }
+
+    // logs once when the set finished loading
+    pub fn poll(&mut self) -> Completion {
+        let completion = self.progress.complete();
+
+        if !self.finished {
+            match completion {
+                Completion::Complete => {
+                    info!("Finished loading {} in the background", self.name);
+                    self.finished = true;
+                }
+                Completion::Failed => {
+                    for (asset, err) in self.progress.errors() {
+                        error!("Failed to load {} for {}: {}", asset, self.name, err);
+                    }
+                    self.finished = true;
+                }
+                Completion::Loading => (),
+            }
+        }
+
+        completion
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.progress.fraction()
+    }
+
+    // only available once everything loaded
+    pub fn get(&self) -> Option<&T> {
+        match self.progress.complete() {
+            Completion::Complete => Some(&self.assets),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use super::PackSource;
+use crate::logger::prelude::*;
+use amethyst::assets::{Asset, AssetStorage, Format, Handle, Loader, Progress};
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+
+struct CacheEntry {
+    handle: Box<dyn Any + Send + Sync>,
+    asset_type: &'static str,
+    // size of the source file, used as an estimate of the memory usage
+    bytes: u64,
+    owners: HashMap<String, usize>,
+}
+
+// used as a resource, hands out existing handles for assets that are already loaded
+pub struct AssetCache {
+    root: PathBuf,
+    pack: Option<PackSource>,
+    entries: HashMap<(String, &'static str), CacheEntry>,
+}
+
+impl AssetCache {
+    pub fn new(root: PathBuf, pack: Option<PackSource>) -> AssetCache {
+        AssetCache {
+            root,
+            pack,
+            entries: HashMap::new(),
+        }
+    }
+
+    // only assets that aren't cached yet are added to the progress
+    #[allow(clippy::too_many_arguments)]
+    pub fn load<A, F, P>(
+        &mut self,
+        owner: &str,
+        path: &str,
+        format: F,
+        options: F::Options,
+        progress: P,
+        loader: &Loader,
+        storage: &AssetStorage<A>,
+    ) -> Handle<A>
+    where
+        A: Asset,
+        F: Format<A>,
+        P: Progress,
+    {
+        let key = (path.to_string(), F::NAME);
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            if let Some(handle) = entry.handle.downcast_ref::<Handle<A>>() {
+                *entry.owners.entry(owner.to_string()).or_insert(0) += 1;
+                return handle.clone();
+            }
+        }
+
+        let handle = loader.load(path, format, options, progress, storage);
+        let bytes = match &self.pack {
+            Some(pack) => pack.size(path).unwrap_or(0),
+            None => fs::metadata(self.root.join(path))
+                .map(|metadata| metadata.len())
+                .unwrap_or(0),
+        };
+
+        let mut owners = HashMap::new();
+        owners.insert(owner.to_string(), 1);
+        self.entries.insert(
+            key,
+            CacheEntry {
+                handle: Box::new(handle.clone()),
+                asset_type: A::NAME,
+                bytes,
+                owners,
+            },
+        );
+
+        handle
+    }
+
+    // the number of cached assets
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // drops every reference held by the owner, assets without owners are evicted.
+    // Assets can own other assets by their path, those are released with them
+    pub fn release(&mut self, owner: &str) {
+        let mut released = vec![owner.to_string()];
+
+        while let Some(owner) = released.pop() {
+            for entry in self.entries.values_mut() {
+                entry.owners.remove(&owner);
+            }
+
+            for ((path, _), entry) in &self.entries {
+                if entry.owners.is_empty() {
+                    released.push(path.clone());
+                }
+            }
+            self.entries.retain(|_, entry| !entry.owners.is_empty());
+        }
+    }
+
+    // forgets every asset, so failed assets are loaded again on the next request
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn log_usage(&self) {
+        let mut types: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+        let mut owners: BTreeMap<&str, usize> = BTreeMap::new();
+
+        for entry in self.entries.values() {
+            let usage = types.entry(entry.asset_type).or_insert((0, 0));
+            usage.0 += 1;
+            usage.1 += entry.bytes;
+
+            for (owner, count) in &entry.owners {
+                *owners.entry(owner).or_insert(0) += count;
+            }
+        }
+
+        for (asset_type, (count, bytes)) in types {
+            info!(
+                "Cached {} {} asset(s), {:.2} KiB",
+                count,
+                asset_type,
+                bytes as f32 / 1024.0
+            );
+        }
+
+        for (owner, count) in owners {
+            info!("{} holds {} cached asset reference(s)", owner, count);
+        }
+    }
+}
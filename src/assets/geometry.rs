@@ -0,0 +1,122 @@
+use crate::bounds::{BoundingBox, BoundingSphere};
+use amethyst::{
+    assets::{Asset, Error, Handle, ProcessingState, Result as AssetResult, SimpleFormat},
+    core::{
+        nalgebra::{Point3, Vector3},
+        specs::prelude::VecStorage,
+    },
+    renderer::{Mesh, MeshData, ObjFormat, PosNormTangTex, PosNormTex},
+};
+
+// the triangles of a mesh kept around on the CPU, for culling and debug drawing
+#[derive(Clone, Default)]
+pub struct MeshGeometry {
+    // three vertices per triangle
+    pub positions: Vec<Point3<f32>>,
+    pub normals: Vec<Vector3<f32>>,
+    // in the mesh's own space
+    pub bounding_box: BoundingBox,
+    pub bounding_sphere: BoundingSphere,
+}
+
+impl Asset for MeshGeometry {
+    const NAME: &'static str = "smc::MeshGeometry";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl From<MeshGeometry> for Result<ProcessingState<MeshGeometry>, Error> {
+    fn from(geometry: MeshGeometry) -> Result<ProcessingState<MeshGeometry>, Error> {
+        Ok(ProcessingState::Loaded(geometry))
+    }
+}
+
+// the obj importer produces an unindexed triangle list
+fn obj_vertices(bytes: Vec<u8>) -> AssetResult<Vec<PosNormTex>> {
+    match ObjFormat.import(bytes, ())? {
+        MeshData::PosNormTex(vertices) => Ok(vertices),
+        _ => Err("Unexpected vertex format".into()),
+    }
+}
+
+// Reads the triangles of an obj file into a MeshGeometry
+#[derive(Clone)]
+pub struct GeometryFormat;
+
+impl SimpleFormat<MeshGeometry> for GeometryFormat {
+    const NAME: &'static str = "GEOMETRY_OBJ";
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> AssetResult<MeshGeometry> {
+        let vertices = obj_vertices(bytes)?;
+        let positions: Vec<_> = vertices
+            .iter()
+            .map(|vertex| Point3::new(vertex.position.x, vertex.position.y, vertex.position.z))
+            .collect();
+
+        Ok(MeshGeometry {
+            bounding_box: BoundingBox::from_points(&positions).unwrap_or_default(),
+            bounding_sphere: BoundingSphere::from_points(&positions).unwrap_or_default(),
+            normals: vertices.iter().map(|vertex| vertex.normal).collect(),
+            positions,
+        })
+    }
+}
+
+// Reads an obj mesh and adds the tangents physically based shading needs
+#[derive(Clone)]
+pub struct TangentObjFormat;
+
+impl SimpleFormat<Mesh> for TangentObjFormat {
+    const NAME: &'static str = "TANGENT_OBJ";
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> AssetResult<MeshData> {
+        let vertices = obj_vertices(bytes)?;
+
+        let mut result = Vec::with_capacity(vertices.len());
+        for triangle in vertices.chunks(3) {
+            let tangent = triangle_tangent(triangle);
+            result.extend(triangle.iter().map(|vertex| PosNormTangTex {
+                position: vertex.position,
+                normal: vertex.normal,
+                tangent: orthogonalize(tangent, vertex.normal),
+                tex_coord: vertex.tex_coord,
+            }));
+        }
+
+        Ok(MeshData::PosNormTangTex(result))
+    }
+}
+
+// the direction the u texture coordinate grows in
+fn triangle_tangent(triangle: &[PosNormTex]) -> Vector3<f32> {
+    if triangle.len() < 3 {
+        return Vector3::x();
+    }
+
+    let edge1 = triangle[1].position - triangle[0].position;
+    let edge2 = triangle[2].position - triangle[0].position;
+    let uv1 = triangle[1].tex_coord - triangle[0].tex_coord;
+    let uv2 = triangle[2].tex_coord - triangle[0].tex_coord;
+
+    let det = uv1.x * uv2.y - uv2.x * uv1.y;
+    // without texture coordinates any direction along the surface will do
+    if det.abs() < 1.0e-8 {
+        return edge1;
+    }
+    (edge1 * uv2.y - edge2 * uv1.y) / det
+}
+
+// makes the tangent perpendicular to the normal
+fn orthogonalize(tangent: Vector3<f32>, normal: Vector3<f32>) -> Vector3<f32> {
+    let tangent = tangent - normal * normal.dot(&tangent);
+    tangent.try_normalize(1.0e-6).unwrap_or_else(|| {
+        let axis = if normal.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::y()
+        };
+        normal.cross(&axis).normalize()
+    })
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub const MANIFEST_FILE: &str = "manifest.ron";
+// binary assets are stored with Git LFS, a checkout without it has these pointer files
+const LFS_POINTER: &str = "version https://git-lfs.github.com/spec/v1";
+const LFS_OID: &str = "oid sha256:";
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum AssetFormat {
+    Ui,
+    Scene,
+    Strings,
+    Tips,
+    Obj,
+    Png,
+    Ttf,
+    Wav,
+    Text,
+}
+
+impl AssetFormat {
+    // guesses the format of an asset from its path inside the assets folder
+    pub fn from_path(path: &str) -> Option<AssetFormat> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+
+        match &*extension {
+            "ron" if path.starts_with("ui/") => Some(AssetFormat::Ui),
+            "ron" if path.starts_with("scene/") => Some(AssetFormat::Scene),
+            "ron" if path.starts_with("text/") && path.ends_with("/strings.ron") => {
+                Some(AssetFormat::Strings)
+            }
+            "ron" if path.starts_with("text/") => Some(AssetFormat::Tips),
+            "obj" => Some(AssetFormat::Obj),
+            "png" => Some(AssetFormat::Png),
+            "ttf" => Some(AssetFormat::Ttf),
+            "wav" => Some(AssetFormat::Wav),
+            "txt" => Some(AssetFormat::Text),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub format: AssetFormat,
+    // sha256 of the file contents, as hex
+    pub checksum: String,
+}
+
+// describes every file inside the assets folder
+#[derive(Default, Deserialize, Serialize)]
+pub struct Manifest {
+    pub assets: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    pub fn entry(&self, path: &str) -> Option<&ManifestEntry> {
+        self.assets.iter().find(|entry| entry.path == path)
+    }
+}
+
+pub fn checksum(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+// the checksum of the file a Git LFS pointer stands for, None for other files
+pub fn lfs_pointer_checksum(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    if !text.starts_with(LFS_POINTER) {
+        return None;
+    }
+
+    text.lines()
+        .find(|line| line.starts_with(LFS_OID))
+        .map(|line| line[LFS_OID.len()..].trim().to_string())
+}
+
+// all files below the directory, as relative paths separated by '/'
+pub fn list_files(dir: &Path) -> io::Result<Vec<String>> {
+    fn walk(root: &Path, dir: &Path, files: &mut Vec<String>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path: PathBuf = entry?.path();
+            if path.is_dir() {
+                walk(root, &path, files)?;
+            } else if let Some(relative) = relative_path(root, &path) {
+                files.push(relative);
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    walk(dir, dir, &mut files)?;
+    files.sort();
+
+    Ok(files)
+}
+
+// the path relative to the root, separated by '/'
+pub fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let components: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    Some(components.join("/"))
+}
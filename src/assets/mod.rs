@@ -0,0 +1,19 @@
+mod background;
+mod cache;
+mod geometry;
+mod manifest;
+mod pack;
+mod progress;
+mod strings;
+mod tips;
+mod validate;
+
+pub use background::BackgroundLoad;
+pub use cache::AssetCache;
+pub use geometry::{GeometryFormat, MeshGeometry, TangentObjFormat};
+pub use manifest::{list_files, relative_path, AssetFormat};
+pub use pack::{build as build_pack, PackSource, PACK_FILE};
+pub use progress::{AssetProgress, PendingAssets};
+pub use strings::StringTable;
+pub use tips::Tips;
+pub use validate::{decode, update_manifest, validate};
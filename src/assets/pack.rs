@@ -0,0 +1,202 @@
+use super::manifest;
+use amethyst::assets::{Error, Source};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
+
+pub const PACK_FILE: &str = "assets.pack";
+const MAGIC: &[u8; 8] = b"SMCPACK1";
+
+// Layout, all numbers are little endian:
+//   magic, entry count (u32),
+//   per entry: path length (u32), path, offset (u64), stored size (u64),
+//              size (u64), compressed (u8), crc32 of the uncompressed data (u32)
+//   followed by the data of every entry
+struct PackEntry {
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+    compressed: bool,
+    checksum: u32,
+}
+
+// Reads assets from a single archive built by `build`
+#[derive(Clone)]
+pub struct PackSource {
+    path: PathBuf,
+    modified: u64,
+    entries: Arc<HashMap<String, PackEntry>>,
+}
+
+impl PackSource {
+    pub fn open(path: &Path) -> io::Result<PackSource> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not an asset pack"));
+        }
+
+        let count = read_u32(&mut reader)?;
+        let mut entries = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            let path_len = read_u32(&mut reader)? as usize;
+            let mut path = vec![0; path_len];
+            reader.read_exact(&mut path)?;
+            let path = String::from_utf8(path)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+            let entry = PackEntry {
+                offset: read_u64(&mut reader)?,
+                stored_size: read_u64(&mut reader)?,
+                size: read_u64(&mut reader)?,
+                compressed: read_u8(&mut reader)? != 0,
+                checksum: read_u32(&mut reader)?,
+            };
+            entries.insert(path, entry);
+        }
+
+        let modified = fs::metadata(path)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        Ok(PackSource {
+            path: path.to_path_buf(),
+            modified,
+            entries: Arc::new(entries),
+        })
+    }
+
+    // uncompressed size of the asset
+    pub fn size(&self, path: &str) -> Option<u64> {
+        self.entries.get(path).map(|entry| entry.size)
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let entry = self.entries.get(path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{} is not packed", path))
+        })?;
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(entry.offset))?;
+
+        let mut stored = vec![0; entry.stored_size as usize];
+        file.read_exact(&mut stored)?;
+
+        let bytes = if entry.compressed {
+            let mut bytes = Vec::with_capacity(entry.size as usize);
+            DeflateDecoder::new(&*stored).read_to_end(&mut bytes)?;
+            bytes
+        } else {
+            stored
+        };
+
+        if crc32fast::hash(&bytes) != entry.checksum {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Checksum mismatch for {}", path),
+            ));
+        }
+
+        Ok(bytes)
+    }
+}
+
+impl Source for PackSource {
+    fn modified(&self, path: &str) -> Result<u64, Error> {
+        if self.entries.contains_key(path) {
+            Ok(self.modified)
+        } else {
+            Err(format!("{} is not packed", path).into())
+        }
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        self.read(path)
+            .map_err(|err| format!("Failed to read {} from {}: {}", path, PACK_FILE, err).into())
+    }
+}
+
+// Packs every file below the directory, compressing the ones that get smaller.
+// Returns the number of packed files
+pub fn build(dir: &Path, output: &Path, compress: bool) -> io::Result<usize> {
+    let mut files = Vec::new();
+    for path in manifest::list_files(dir)? {
+        let bytes = fs::read(dir.join(&path))?;
+        let checksum = crc32fast::hash(&bytes);
+        let size = bytes.len() as u64;
+
+        let mut stored = bytes;
+        let mut compressed = false;
+        if compress {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(&stored)?;
+            let deflated = encoder.finish()?;
+            if deflated.len() < stored.len() {
+                stored = deflated;
+                compressed = true;
+            }
+        }
+
+        files.push((path, stored, size, compressed, checksum));
+    }
+
+    let index_len: usize = files
+        .iter()
+        .map(|(path, ..)| 4 + path.len() + 8 + 8 + 8 + 1 + 4)
+        .sum();
+    let mut offset = (MAGIC.len() + 4 + index_len) as u64;
+
+    let mut writer = BufWriter::new(File::create(output)?);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&(files.len() as u32).to_le_bytes())?;
+    for (path, stored, size, compressed, checksum) in &files {
+        writer.write_all(&(path.len() as u32).to_le_bytes())?;
+        writer.write_all(path.as_bytes())?;
+        writer.write_all(&offset.to_le_bytes())?;
+        writer.write_all(&(stored.len() as u64).to_le_bytes())?;
+        writer.write_all(&size.to_le_bytes())?;
+        writer.write_all(&[*compressed as u8])?;
+        writer.write_all(&checksum.to_le_bytes())?;
+
+        offset += stored.len() as u64;
+    }
+
+    for (_, stored, ..) in &files {
+        writer.write_all(stored)?;
+    }
+    writer.flush()?;
+
+    Ok(files.len())
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
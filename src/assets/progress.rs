@@ -0,0 +1,130 @@
+use amethyst::assets::{Completion, Error, Progress, ProgressCounter, Tracker};
+use std::sync::{Arc, Mutex};
+
+// A ProgressCounter that also remembers the names of the assets that are still loading
+#[derive(Default)]
+pub struct AssetProgress {
+    counter: ProgressCounter,
+    pending: PendingAssets,
+}
+
+impl AssetProgress {
+    pub fn new() -> AssetProgress {
+        AssetProgress::default()
+    }
+
+    // the name is shown while the asset is loading
+    pub fn named(&mut self, name: impl Into<String>) -> NamedProgress<'_> {
+        self.pending.named(&mut self.counter, name)
+    }
+
+    // for naming the sub-assets of prefabs loaded with this progress
+    pub fn pending(&self) -> PendingAssets {
+        self.pending.clone()
+    }
+
+    pub fn complete(&self) -> Completion {
+        self.counter.complete()
+    }
+
+    // between 0.0 and 1.0
+    pub fn fraction(&self) -> f32 {
+        match self.counter.num_assets() {
+            0 => 1.0,
+            assets => self.counter.num_finished() as f32 / assets as f32,
+        }
+    }
+
+    // the asset that started loading last, sub-assets come after their prefab
+    pub fn current(&self) -> Option<String> {
+        self.pending.0.lock().unwrap().last().cloned()
+    }
+
+    // pairs of asset name and error message
+    pub fn errors(&self) -> Vec<(String, String)> {
+        self.counter
+            .errors()
+            .into_iter()
+            .map(|meta| (meta.asset_name, meta.error.to_string()))
+            .collect()
+    }
+}
+
+// The names of the assets that are still loading. Shared with the sub-assets of prefabs,
+// which amethyst loads with its own ProgressCounter
+#[derive(Clone, Default)]
+pub struct PendingAssets(Arc<Mutex<Vec<String>>>);
+
+impl PendingAssets {
+    // counts the asset with the counter, the name is shown while it's loading
+    pub fn named<'a>(
+        &self,
+        counter: &'a mut ProgressCounter,
+        name: impl Into<String>,
+    ) -> NamedProgress<'a> {
+        NamedProgress {
+            counter,
+            pending: self.clone(),
+            name: name.into(),
+        }
+    }
+}
+
+pub struct NamedProgress<'a> {
+    counter: &'a mut ProgressCounter,
+    pending: PendingAssets,
+    name: String,
+}
+
+impl<'a> Progress for NamedProgress<'a> {
+    type Tracker = NamedTracker;
+
+    fn add_assets(&mut self, num: usize) {
+        (&mut *self.counter).add_assets(num);
+    }
+
+    fn create_tracker(self) -> NamedTracker {
+        self.pending.0.lock().unwrap().push(self.name.clone());
+
+        NamedTracker {
+            inner: self.counter.create_tracker(),
+            name: self.name,
+            pending: self.pending,
+        }
+    }
+}
+
+pub struct NamedTracker {
+    inner: <&'static mut ProgressCounter as Progress>::Tracker,
+    name: String,
+    pending: PendingAssets,
+}
+
+impl NamedTracker {
+    fn finish(&self) {
+        let mut pending = self.pending.0.lock().unwrap();
+        if let Some(index) = pending.iter().position(|name| *name == self.name) {
+            pending.remove(index);
+        }
+    }
+}
+
+impl Tracker for NamedTracker {
+    fn success(self: Box<Self>) {
+        let tracker = *self;
+        tracker.finish();
+        Box::new(tracker.inner).success();
+    }
+
+    fn fail(
+        self: Box<Self>,
+        handle_id: u32,
+        asset_type_name: &'static str,
+        asset_name: String,
+        error: Error,
+    ) {
+        let tracker = *self;
+        tracker.finish();
+        Box::new(tracker.inner).fail(handle_id, asset_type_name, asset_name, error);
+    }
+}
@@ -0,0 +1,22 @@
+use amethyst::{
+    assets::{Asset, Error, Handle, ProcessingState},
+    core::specs::prelude::VecStorage,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// the translated UI strings of one language, by key
+#[derive(Clone, Default, Deserialize)]
+pub struct StringTable(pub HashMap<String, String>);
+
+impl Asset for StringTable {
+    const NAME: &'static str = "smc::StringTable";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl From<StringTable> for Result<ProcessingState<StringTable>, Error> {
+    fn from(table: StringTable) -> Result<ProcessingState<StringTable>, Error> {
+        Ok(ProcessingState::Loaded(table))
+    }
+}
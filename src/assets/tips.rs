@@ -0,0 +1,21 @@
+use amethyst::{
+    assets::{Asset, Error, Handle, ProcessingState},
+    core::specs::prelude::VecStorage,
+};
+use serde::Deserialize;
+
+// loading screen tips, one file per language
+#[derive(Clone, Default, Deserialize)]
+pub struct Tips(pub Vec<String>);
+
+impl Asset for Tips {
+    const NAME: &'static str = "smc::Tips";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl From<Tips> for Result<ProcessingState<Tips>, Error> {
+    fn from(tips: Tips) -> Result<ProcessingState<Tips>, Error> {
+        Ok(ProcessingState::Loaded(tips))
+    }
+}
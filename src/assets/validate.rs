@@ -0,0 +1,294 @@
+use super::{
+    manifest::{self, AssetFormat, Manifest, ManifestEntry, MANIFEST_FILE},
+    StringTable, Tips,
+};
+use crate::{
+    ecs::daynight::DayNightConfig,
+    localization::{strings_path, LANGUAGES},
+    logger::prelude::*,
+    scene::ScenePrefabData,
+    settings::Settings,
+    states::{BenchmarkConfig, LoadingConfig},
+    ui::CustomUi,
+};
+use amethyst::{
+    assets::{Prefab, SimpleFormat},
+    audio::{AudioFormat, WavFormat},
+    config::Config,
+    input::Bindings,
+    renderer::{DisplayConfig, ObjFormat, PngFormat, TextureFormat, TextureMetadata},
+    ui::{FontFormat, TtfFormat, UiWidget},
+};
+use std::{fs, path::Path};
+
+// assets loaded by a path in the code, they aren't referenced by other assets
+const CODE_REFERENCES: &[&str] = &[
+    "ui/debug.ron",
+    "ui/loading.ron",
+    "ui/loading_error.ron",
+    "ui/mainmenu.ron",
+    "ui/settings.ron",
+    "scene/mainmenu.ron",
+    "scene/gameplay.ron",
+];
+
+// Checks the manifest, the assets and the config files without opening a window,
+// returns the number of problems found
+pub fn validate(assets_dir: &Path, config_dir: &Path) -> usize {
+    let mut problems = Vec::new();
+
+    match Manifest::load_no_fallback(assets_dir.join(MANIFEST_FILE)) {
+        Ok(manifest) => {
+            validate_assets(&manifest, assets_dir, &mut problems);
+            validate_references(&manifest, config_dir, &mut problems);
+        }
+        Err(err) => problems.push(format!("Failed to load {}: {}", MANIFEST_FILE, err)),
+    }
+    validate_config(config_dir, &mut problems);
+
+    for problem in &problems {
+        error!("{}", problem);
+    }
+
+    if problems.is_empty() {
+        info!("All assets and config files are valid");
+    } else {
+        error!("Found {} problem(s)", problems.len());
+    }
+
+    problems.len()
+}
+
+// adds new files to the manifest and refreshes the checksums of the existing ones
+pub fn update_manifest(assets_dir: &Path) {
+    let manifest_path = assets_dir.join(MANIFEST_FILE);
+    let old = Manifest::load_no_fallback(&manifest_path).unwrap_or_default();
+
+    let mut manifest = Manifest::default();
+    for path in manifest::list_files(assets_dir).unwrap_log("Failed to list assets") {
+        if path == MANIFEST_FILE {
+            continue;
+        }
+
+        let format = match old.entry(&path) {
+            Some(entry) => entry.format,
+            None => match AssetFormat::from_path(&path) {
+                Some(format) => format,
+                None => {
+                    warn!("Skipping {}, unknown format", path);
+                    continue;
+                }
+            },
+        };
+
+        let bytes = fs::read(assets_dir.join(&path)).unwrap_log(format!("Failed to read {}", path));
+        let checksum = match manifest::lfs_pointer_checksum(&bytes) {
+            Some(checksum) => {
+                warn!("{} is a Git LFS pointer, using the checksum it points to", path);
+                checksum
+            }
+            None => manifest::checksum(&bytes),
+        };
+        manifest.assets.push(ManifestEntry {
+            checksum,
+            path,
+            format,
+        });
+    }
+
+    manifest
+        .write(&manifest_path)
+        .unwrap_log("Failed to write manifest");
+    info!("Wrote {} entries to {}", manifest.assets.len(), MANIFEST_FILE);
+}
+
+fn validate_assets(manifest: &Manifest, assets_dir: &Path, problems: &mut Vec<String>) {
+    for entry in &manifest.assets {
+        let bytes = match fs::read(assets_dir.join(&entry.path)) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                problems.push(format!("Failed to read {}: {}", entry.path, err));
+                continue;
+            }
+        };
+
+        if manifest::lfs_pointer_checksum(&bytes).is_some() {
+            problems.push(format!(
+                "{} is a Git LFS pointer, fetch the assets with git lfs pull",
+                entry.path
+            ));
+            continue;
+        }
+
+        let checksum = manifest::checksum(&bytes);
+        if checksum != entry.checksum {
+            problems.push(format!(
+                "Checksum mismatch for {}: expected {}, found {}",
+                entry.path, entry.checksum, checksum
+            ));
+        }
+
+        for reference in references(entry.format, &bytes) {
+            if manifest.entry(&reference).is_none() {
+                problems.push(format!(
+                    "{} references {}, which is not in the manifest",
+                    entry.path, reference
+                ));
+            }
+        }
+
+        if let Err(err) = decode(entry.format, bytes) {
+            problems.push(format!("Failed to decode {}: {}", entry.path, err));
+        }
+    }
+
+    match manifest::list_files(assets_dir) {
+        Ok(files) => {
+            for path in files {
+                if path != MANIFEST_FILE && manifest.entry(&path).is_none() {
+                    problems.push(format!("{} is not in the manifest", path));
+                }
+            }
+        }
+        Err(err) => problems.push(format!("Failed to list assets: {}", err)),
+    }
+}
+
+// the assets the code and the config files load by path
+fn validate_references(manifest: &Manifest, config_dir: &Path, problems: &mut Vec<String>) {
+    let mut references: Vec<(String, String)> = CODE_REFERENCES
+        .iter()
+        .map(|path| ("The code".to_string(), path.to_string()))
+        .collect();
+    for language in LANGUAGES {
+        references.push(("The code".into(), strings_path(language)));
+        references.push(("The code".into(), format!("text/{}/tips.ron", language)));
+    }
+
+    if let Ok(loading) = LoadingConfig::load_no_fallback(config_dir.join("loading.ron")) {
+        for background in loading.backgrounds {
+            references.push(("loading.ron".into(), background));
+        }
+    }
+    if let Ok(benchmark) = BenchmarkConfig::load_no_fallback(config_dir.join("benchmark.ron")) {
+        references.push((
+            "benchmark.ron".into(),
+            format!("scene/{}.ron", benchmark.scene),
+        ));
+    }
+
+    for (source, reference) in references {
+        if manifest.entry(&reference).is_none() {
+            problems.push(format!(
+                "{} references {}, which is not in the manifest",
+                source, reference
+            ));
+        }
+    }
+}
+
+fn validate_config(config_dir: &Path, problems: &mut Vec<String>) {
+    let mut check = |name: &str, result: Result<(), String>| {
+        if let Err(err) = result {
+            problems.push(format!("Failed to parse config {}: {}", name, err));
+        }
+    };
+
+    check(
+        "display.ron",
+        DisplayConfig::load_no_fallback(config_dir.join("display.ron"))
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+    );
+    check(
+        "key_bindings.ron",
+        Bindings::<String, String>::load_no_fallback(config_dir.join("key_bindings.ron"))
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+    );
+    check(
+        "loading.ron",
+        LoadingConfig::load_no_fallback(config_dir.join("loading.ron"))
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+    );
+    check(
+        "settings.ron",
+        Settings::load_no_fallback(config_dir.join("settings.ron"))
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+    );
+    check(
+        "benchmark.ron",
+        BenchmarkConfig::load_no_fallback(config_dir.join("benchmark.ron"))
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+    );
+    check(
+        "day_night.ron",
+        DayNightConfig::load_no_fallback(config_dir.join("day_night.ron"))
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+    );
+}
+
+// checks that the bytes can be loaded as the given format
+pub fn decode(format: AssetFormat, bytes: Vec<u8>) -> Result<(), String> {
+    match format {
+        AssetFormat::Ui => ron::de::from_bytes::<
+            UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>,
+        >(&bytes)
+        .map(|_| ())
+        .map_err(|err| err.to_string()),
+        AssetFormat::Scene => ron::de::from_bytes::<Prefab<ScenePrefabData>>(&bytes)
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        AssetFormat::Strings => ron::de::from_bytes::<StringTable>(&bytes)
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        AssetFormat::Tips => ron::de::from_bytes::<Tips>(&bytes)
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        AssetFormat::Obj => ObjFormat
+            .import(bytes, ())
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        AssetFormat::Png => PngFormat
+            .import(bytes, TextureMetadata::srgb())
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        AssetFormat::Ttf => TtfFormat
+            .import(bytes, ())
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        AssetFormat::Wav => WavFormat
+            .import(bytes, ())
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+        AssetFormat::Text => String::from_utf8(bytes)
+            .map(|_| ())
+            .map_err(|err| err.to_string()),
+    }
+}
+
+// paths of the assets a RON file depends on
+fn references(format: AssetFormat, bytes: &[u8]) -> Vec<String> {
+    let patterns: &[&str] = match format {
+        AssetFormat::Ui => &["File(\""],
+        AssetFormat::Scene => &["mesh: \""],
+        _ => &[],
+    };
+
+    let text = String::from_utf8_lossy(bytes);
+    let mut references = Vec::new();
+    for pattern in patterns {
+        for (index, _) in text.match_indices(pattern) {
+            let start = index + pattern.len();
+            if let Some(length) = text[start..].find('"') {
+                references.push(text[start..start + length].to_string());
+            }
+        }
+    }
+
+    references
+}
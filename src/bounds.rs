@@ -0,0 +1,310 @@
+use amethyst::core::nalgebra::{Matrix4, Point3, Vector4};
+
+// an axis aligned box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Default for BoundingBox {
+    fn default() -> Self {
+        BoundingBox {
+            min: Point3::origin(),
+            max: Point3::origin(),
+        }
+    }
+}
+
+impl BoundingBox {
+    pub fn from_points(points: &[Point3<f32>]) -> Option<BoundingBox> {
+        let first = *points.first()?;
+        let mut bounds = BoundingBox {
+            min: first,
+            max: first,
+        };
+
+        for point in &points[1..] {
+            for axis in 0..3 {
+                bounds.min.coords[axis] = bounds.min.coords[axis].min(point.coords[axis]);
+                bounds.max.coords[axis] = bounds.max.coords[axis].max(point.coords[axis]);
+            }
+        }
+        Some(bounds)
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        Point3::from_coordinates((self.min.coords + self.max.coords) * 0.5)
+    }
+
+    pub fn corners(&self) -> [Point3<f32>; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Point3::new(min.x, min.y, min.z),
+            Point3::new(max.x, min.y, min.z),
+            Point3::new(min.x, max.y, min.z),
+            Point3::new(max.x, max.y, min.z),
+            Point3::new(min.x, min.y, max.z),
+            Point3::new(max.x, min.y, max.z),
+            Point3::new(min.x, max.y, max.z),
+            Point3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    // the axis aligned box around the transformed corners
+    pub fn transformed(&self, matrix: &Matrix4<f32>) -> BoundingBox {
+        let corners: Vec<_> = self
+            .corners()
+            .iter()
+            .map(|corner| matrix.transform_point(corner))
+            .collect();
+        BoundingBox::from_points(&corners).unwrap()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+}
+
+impl Default for BoundingSphere {
+    fn default() -> Self {
+        BoundingSphere {
+            center: Point3::origin(),
+            radius: 0.0,
+        }
+    }
+}
+
+impl BoundingSphere {
+    // centered on the bounding box, not the smallest sphere but close enough for culling
+    pub fn from_points(points: &[Point3<f32>]) -> Option<BoundingSphere> {
+        let center = BoundingBox::from_points(points)?.center();
+        let radius = points
+            .iter()
+            .map(|point| (point - center).norm())
+            .fold(0.0, f32::max);
+
+        Some(BoundingSphere { center, radius })
+    }
+
+    // scaled by the largest scale of the matrix, so it still covers everything
+    pub fn transformed(&self, matrix: &Matrix4<f32>) -> BoundingSphere {
+        let scale = (0..3)
+            .map(|axis| matrix.column(axis).xyz().norm())
+            .fold(0.0, f32::max);
+
+        BoundingSphere {
+            center: matrix.transform_point(&self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+// the six planes of a view frustum, with normals pointing inwards
+#[derive(Clone, Debug)]
+pub struct Frustum {
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    // extracts the planes from a projection * view matrix, with OpenGL's depth range
+    pub fn from_matrix(matrix: &Matrix4<f32>) -> Frustum {
+        let row = |index: usize| matrix.row(index).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+        let mut planes = [w + x, w - x, w + y, w - y, w + z, w - z];
+        for plane in planes.iter_mut() {
+            let length = plane.xyz().norm();
+            if length > 0.0 {
+                *plane /= length;
+            }
+        }
+        Frustum { planes }
+    }
+
+    // positive in front of the plane
+    fn distance(plane: &Vector4<f32>, point: &Point3<f32>) -> f32 {
+        plane.xyz().dot(&point.coords) + plane.w
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| Frustum::distance(plane, &sphere.center) >= -sphere.radius)
+    }
+
+    // may report boxes near the corners of the frustum as intersecting
+    pub fn intersects_box(&self, bounds: &BoundingBox) -> bool {
+        self.planes.iter().all(|plane| {
+            // the corner furthest along the plane's normal
+            let mut corner = bounds.min;
+            for axis in 0..3 {
+                if plane[axis] >= 0.0 {
+                    corner.coords[axis] = bounds.max.coords[axis];
+                }
+            }
+            Frustum::distance(plane, &corner) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::core::nalgebra::{Perspective3, Vector3};
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1.0e-4
+    }
+
+    fn approx_point(a: Point3<f32>, b: Point3<f32>) -> bool {
+        (0..3).all(|axis| approx(a.coords[axis], b.coords[axis]))
+    }
+
+    // a camera at z = 5 looking down -z, a 90 degree fov reaches as far to the side
+    // as it looks ahead. The near plane is at z = 4, the far plane at z = -95
+    fn frustum() -> Frustum {
+        let projection = Perspective3::new(1.0, FRAC_PI_2, 1.0, 100.0).to_homogeneous();
+        let view = Matrix4::new_translation(&Vector3::new(0.0, 0.0, -5.0));
+        Frustum::from_matrix(&(projection * view))
+    }
+
+    fn sphere(x: f32, y: f32, z: f32, radius: f32) -> BoundingSphere {
+        BoundingSphere {
+            center: Point3::new(x, y, z),
+            radius,
+        }
+    }
+
+    fn cube(x: f32, y: f32, z: f32, half_size: f32) -> BoundingBox {
+        let half = Vector3::new(half_size, half_size, half_size);
+        BoundingBox {
+            min: Point3::new(x, y, z) - half,
+            max: Point3::new(x, y, z) + half,
+        }
+    }
+
+    // centers outside of each plane, and next to it so they reach across
+    const OUTSIDE: &[(f32, f32, f32)] = &[
+        (-20.0, 0.0, 0.0),
+        (20.0, 0.0, 0.0),
+        (0.0, -20.0, 0.0),
+        (0.0, 20.0, 0.0),
+        (0.0, 0.0, 7.0),
+        (0.0, 0.0, -100.0),
+    ];
+    const STRADDLING: &[(f32, f32, f32)] = &[
+        (-5.5, 0.0, 0.0),
+        (5.5, 0.0, 0.0),
+        (0.0, -5.5, 0.0),
+        (0.0, 5.5, 0.0),
+        (0.0, 0.0, 4.2),
+        (0.0, 0.0, -95.5),
+    ];
+
+    #[test]
+    fn sphere_inside() {
+        assert!(frustum().intersects_sphere(&sphere(0.0, 0.0, 0.0, 1.0)));
+        assert!(frustum().intersects_sphere(&sphere(3.0, -3.0, -50.0, 0.1)));
+    }
+
+    #[test]
+    fn sphere_outside() {
+        for &(x, y, z) in OUTSIDE {
+            assert!(
+                !frustum().intersects_sphere(&sphere(x, y, z, 1.0)),
+                "{} {} {}",
+                x,
+                y,
+                z
+            );
+        }
+    }
+
+    #[test]
+    fn sphere_straddling() {
+        for &(x, y, z) in STRADDLING {
+            assert!(
+                frustum().intersects_sphere(&sphere(x, y, z, 1.0)),
+                "{} {} {}",
+                x,
+                y,
+                z
+            );
+        }
+    }
+
+    #[test]
+    fn box_inside() {
+        assert!(frustum().intersects_box(&cube(0.0, 0.0, 0.0, 1.0)));
+        assert!(frustum().intersects_box(&cube(3.0, -3.0, -50.0, 0.1)));
+    }
+
+    #[test]
+    fn box_outside() {
+        for &(x, y, z) in OUTSIDE {
+            assert!(
+                !frustum().intersects_box(&cube(x, y, z, 1.0)),
+                "{} {} {}",
+                x,
+                y,
+                z
+            );
+        }
+    }
+
+    #[test]
+    fn box_straddling() {
+        for &(x, y, z) in STRADDLING {
+            assert!(
+                frustum().intersects_box(&cube(x, y, z, 1.0)),
+                "{} {} {}",
+                x,
+                y,
+                z
+            );
+        }
+    }
+
+    #[test]
+    fn sphere_non_uniform_scale() {
+        let matrix = Matrix4::new_translation(&Vector3::new(0.0, 0.0, 10.0))
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, 3.0, 2.0));
+        let transformed = sphere(1.0, 1.0, 0.0, 1.0).transformed(&matrix);
+
+        assert!(approx_point(
+            transformed.center,
+            Point3::new(1.0, 3.0, 10.0)
+        ));
+        // covers the sphere stretched along y
+        assert!(approx(transformed.radius, 3.0));
+    }
+
+    #[test]
+    fn box_rotated() {
+        let quarter = Matrix4::new_rotation(Vector3::z() * FRAC_PI_2);
+        let bounds = BoundingBox {
+            min: Point3::new(0.0, 0.0, 0.0),
+            max: Point3::new(2.0, 1.0, 1.0),
+        };
+        let transformed = bounds.transformed(&quarter);
+        assert!(approx_point(transformed.min, Point3::new(-1.0, 0.0, 0.0)));
+        assert!(approx_point(transformed.max, Point3::new(0.0, 2.0, 1.0)));
+
+        // the corners of a turned cube stick out further
+        let eighth = Matrix4::new_rotation(Vector3::z() * FRAC_PI_4);
+        let transformed = cube(0.0, 0.0, 0.0, 1.0).transformed(&eighth);
+        let diagonal = 2.0f32.sqrt();
+        assert!(approx_point(
+            transformed.min,
+            Point3::new(-diagonal, -diagonal, -1.0)
+        ));
+        assert!(approx_point(
+            transformed.max,
+            Point3::new(diagonal, diagonal, 1.0)
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::settings::{DisplayMode, DisplaySettings, Settings, SettingsOverrides};
+use amethyst::{
+    core::{shrev::EventChannel, specs::prelude::*, timing::Time},
+    renderer::WindowMessages,
+    winit::{
+        dpi::{LogicalPosition, LogicalSize},
+        Event, MonitorId, Window, WindowEvent,
+    },
+};
+use std::sync::{Arc, Mutex};
+
+// offered in the settings screen, if they fit on the monitor
+const RESOLUTIONS: &[(u32, u32)] = &[
+    (1024, 768),
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+// a moved or resized window is saved once it was left alone for this many seconds
+const SAVE_DELAY: f32 = 1.0;
+
+#[derive(Clone)]
+pub struct Monitor {
+    pub name: String,
+    // in logical pixels
+    pub size: (u32, u32),
+}
+
+// used as a resource, the monitors the window can be put on
+#[derive(Default)]
+pub struct Monitors(pub Vec<Monitor>);
+
+impl Monitors {
+    // the resolutions fitting on the monitor, plus the current one
+    pub fn resolutions(&self, monitor: usize, current: Option<(u32, u32)>) -> Vec<(u32, u32)> {
+        let size = self.0.get(monitor).map(|monitor| monitor.size);
+        let mut resolutions: Vec<_> = RESOLUTIONS
+            .iter()
+            .cloned()
+            .filter(|&(width, height)| {
+                size.map_or(true, |(max_width, max_height)| {
+                    width <= max_width && height <= max_height
+                })
+            })
+            .collect();
+
+        if let Some(current) = current {
+            if !resolutions.contains(&current) {
+                resolutions.push(current);
+                resolutions.sort();
+            }
+        }
+        resolutions
+    }
+}
+
+fn select_monitor(window: &Window, index: usize) -> MonitorId {
+    window
+        .get_available_monitors()
+        .nth(index)
+        .unwrap_or_else(|| window.get_primary_monitor())
+}
+
+fn apply(window: &Window, display: &DisplaySettings) {
+    let monitor = select_monitor(window, display.monitor);
+    let dpi = monitor.get_hidpi_factor();
+    let monitor_position = monitor.get_position().to_logical(dpi);
+    let monitor_size = monitor.get_dimensions().to_logical(dpi);
+
+    match display.mode {
+        DisplayMode::DesktopFullscreen => {
+            window.set_decorations(false);
+            window.set_fullscreen(Some(monitor));
+        }
+        DisplayMode::Borderless => {
+            window.set_fullscreen(None);
+            window.set_decorations(false);
+            window.set_position(monitor_position);
+            window.set_inner_size(monitor_size);
+        }
+        DisplayMode::Windowed => {
+            window.set_fullscreen(None);
+            window.set_decorations(true);
+            if let Some((width, height)) = display.resolution {
+                window.set_inner_size(LogicalSize::new(width.into(), height.into()));
+            }
+
+            let position = match display.position {
+                Some((x, y)) => LogicalPosition::new(x.into(), y.into()),
+                // centered on the monitor
+                None => {
+                    let size = window
+                        .get_outer_size()
+                        .unwrap_or_else(|| LogicalSize::new(0.0, 0.0));
+                    LogicalPosition::new(
+                        monitor_position.x + (monitor_size.width - size.width).max(0.0) / 2.0,
+                        monitor_position.y + (monitor_size.height - size.height).max(0.0) / 2.0,
+                    )
+                }
+            };
+            window.set_position(position);
+        }
+    }
+}
+
+// Applies the display settings to the window whenever they change, and keeps the
+// window's size and position in the settings while in windowed mode. A window set up
+// by the command line isn't kept, it would replace the player's settings
+#[derive(Default)]
+pub struct DisplaySystem {
+    event_reader: Option<ReaderId<Event>>,
+    applied: Option<DisplaySettings>,
+    // filled in by a window command, as only the render system has the window
+    queried_monitors: Arc<Mutex<Option<Vec<Monitor>>>>,
+    // seconds until the window's size and position are saved
+    save_timer: Option<f32>,
+}
+
+impl<'a> System<'a> for DisplaySystem {
+    type SystemData = (
+        Read<'a, EventChannel<Event>>,
+        Read<'a, Time>,
+        Write<'a, Settings>,
+        Read<'a, SettingsOverrides>,
+        Write<'a, Monitors>,
+        Write<'a, WindowMessages>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(res.fetch_mut::<EventChannel<Event>>().register_reader());
+    }
+
+    fn run(
+        &mut self,
+        (events, time, mut settings, overrides, mut monitors, mut messages): Self::SystemData,
+    ) {
+        if let Some(queried) = self.queried_monitors.lock().unwrap().take() {
+            monitors.0 = queried;
+        }
+
+        let windowed =
+            settings.display.mode == DisplayMode::Windowed && !overrides.overrides_display();
+        let mut changed = false;
+        for event in events.read(self.event_reader.as_mut().unwrap()) {
+            let display = &mut settings.display;
+            match event {
+                Event::WindowEvent {
+                    event: WindowEvent::Resized(size),
+                    ..
+                } if windowed => {
+                    let resolution = Some((size.width.round() as u32, size.height.round() as u32));
+                    changed |= display.resolution != resolution;
+                    display.resolution = resolution;
+                }
+                Event::WindowEvent {
+                    event: WindowEvent::Moved(position),
+                    ..
+                } if windowed => {
+                    let position = Some((position.x.round() as i32, position.y.round() as i32));
+                    changed |= display.position != position;
+                    display.position = position;
+                }
+                _ => (),
+            }
+        }
+
+        let display = overrides.display(&settings.display);
+        // the window is already where the settings say
+        if changed && self.applied.is_some() {
+            self.applied = Some(display.clone());
+            self.save_timer = Some(SAVE_DELAY);
+        }
+
+        if self.applied.as_ref() != Some(&display) {
+            if self.applied.is_none() {
+                let queried = self.queried_monitors.clone();
+                messages.send_command(move |window| {
+                    let monitors = window
+                        .get_available_monitors()
+                        .enumerate()
+                        .map(|(index, monitor)| {
+                            let size = monitor
+                                .get_dimensions()
+                                .to_logical(monitor.get_hidpi_factor());
+                            Monitor {
+                                name: monitor
+                                    .get_name()
+                                    .unwrap_or_else(|| format!("Monitor {}", index + 1)),
+                                size: (size.width.round() as u32, size.height.round() as u32),
+                            }
+                        })
+                        .collect();
+                    *queried.lock().unwrap() = Some(monitors);
+                });
+            }
+
+            self.applied = Some(display.clone());
+            messages.send_command(move |window| apply(window, &display));
+        }
+
+        if let Some(timer) = self.save_timer.as_mut() {
+            *timer -= time.delta_real_seconds();
+            if *timer <= 0.0 {
+                settings.save();
+                self.save_timer = None;
+            }
+        }
+    }
+}
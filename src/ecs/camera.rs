@@ -1,42 +1,81 @@
+use super::CurrentState;
+use crate::{
+    graphics,
+    settings::{CameraSettings, Settings},
+};
 use amethyst::{
-    core::{nalgebra::Perspective3, shrev::EventChannel, specs::prelude::*},
-    renderer::{Camera, Event, WindowEvent},
+    controls::{HideCursor, WindowFocus},
+    core::{specs::prelude::*, timing::Time},
+    input::InputHandler,
+    renderer::{Camera, ScreenDimensions},
 };
 
-// Updates the Camera with a new Projection Matrix to fit the Screen
-pub struct CameraAspect {
-    event_reader: Option<ReaderId<Event>>,
+// Updates the projection of every camera to fit the screen and the camera settings.
+// The field of view widens while sprinting and narrows while zooming
+#[derive(Default)]
+pub struct CameraProjection {
+    // the current field of view, eased towards the target
+    fov: Option<f32>,
+    // what the projection was last built from
+    applied: Option<(f32, f32, CameraSettings)>,
 }
 
-impl CameraAspect {
-    pub fn new() -> Self {
-        CameraAspect { event_reader: None }
-    }
-}
+impl<'a> System<'a> for CameraProjection {
+    type SystemData = (
+        ReadExpect<'a, CurrentState>,
+        Read<'a, Time>,
+        ReadExpect<'a, ScreenDimensions>,
+        Read<'a, Settings>,
+        Read<'a, InputHandler<String, String>>,
+        Read<'a, WindowFocus>,
+        Read<'a, HideCursor>,
+        WriteStorage<'a, Camera>,
+    );
 
-impl<'a> System<'a> for CameraAspect {
-    type SystemData = (WriteStorage<'a, Camera>, Read<'a, EventChannel<Event>>);
+    fn run(
+        &mut self,
+        (
+            cur_state,
+            time,
+            dimensions,
+            settings,
+            input_handler,
+            focus,
+            hide,
+            mut cameras,
+        ): Self::SystemData,
+    ) {
+        let camera = &settings.camera;
+        let controlling = *cur_state == CurrentState::Gameplay && focus.is_focused && hide.hide;
+        let action = |name| controlling && input_handler.action_is_down(name).unwrap_or(false);
 
-    fn setup(&mut self, res: &mut Resources) {
-        Self::SystemData::setup(res);
-        self.event_reader = Some(res.fetch_mut::<EventChannel<Event>>().register_reader());
-    }
+        let target = if action("zoom") {
+            camera.zoom_fov
+        } else if action("sprint") {
+            camera.fov + camera.sprint_fov_kick
+        } else {
+            camera.fov
+        };
 
-    fn run(&mut self, (mut cameras, event_channel): Self::SystemData) {
-        for event in event_channel.read(self.event_reader.as_mut().unwrap()) {
-            if let Event::WindowEvent {
-                event: WindowEvent::Resized(size),
-                ..
-            } = event
-            {
-                let (width, height): (u32, u32) = (*size).into();
-                for camera in (&mut cameras).join() {
-                    let mut projection = Perspective3::from_matrix_unchecked(camera.proj);
-                    projection.set_aspect(width as f32 / height as f32);
+        let fov = self.fov.get_or_insert(target);
+        let smoothing = 1.0 - (-camera.fov_smoothing * time.delta_real_seconds()).exp();
+        *fov += (target - *fov) * smoothing;
+        if (target - *fov).abs() < 0.01 {
+            *fov = target;
+        }
+        let fov = *fov;
 
-                    camera.proj = projection.to_homogeneous();
-                }
+        let aspect = dimensions.aspect_ratio();
+        if let Some((applied_aspect, applied_fov, ref applied)) = self.applied {
+            if applied_aspect == aspect && applied_fov == fov && applied == camera {
+                return;
             }
         }
+
+        let projection = graphics::projection(aspect, fov, camera);
+        for camera in (&mut cameras).join() {
+            camera.proj = projection;
+        }
+        self.applied = Some((aspect, fov, camera.clone()));
     }
 }
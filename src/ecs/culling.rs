@@ -0,0 +1,265 @@
+use super::debug::DebugStats;
+use crate::{
+    assets::MeshGeometry,
+    bounds::{BoundingBox, BoundingSphere, Frustum},
+    settings::Settings,
+};
+use amethyst::{
+    assets::{AssetStorage, Handle},
+    core::{
+        nalgebra::{Matrix4, Vector3},
+        specs::prelude::*,
+        GlobalTransform,
+    },
+    renderer::{Camera, Hidden, Mesh},
+};
+use std::cmp::Ordering;
+
+// marks meshes hidden by the CullingSystem, so it doesn't show meshes hidden by others
+#[derive(Default)]
+pub struct Culled;
+
+impl Component for Culled {
+    type Storage = NullStorage<Self>;
+}
+
+#[derive(Clone)]
+pub struct LodLevel<M = Handle<Mesh>, G = Handle<MeshGeometry>> {
+    // used from this distance to the camera on
+    pub distance: f32,
+    pub mesh: M,
+    // the bounds and the wireframe of the mesh
+    pub geometry: G,
+}
+
+// switches the entity's mesh and geometry by the distance to the camera
+#[derive(Clone)]
+pub struct Lod<M = Handle<Mesh>, G = Handle<MeshGeometry>> {
+    // sorted by distance, the first level is used up close
+    pub levels: Vec<LodLevel<M, G>>,
+}
+
+impl<M: Send + Sync + 'static, G: Send + Sync + 'static> Component for Lod<M, G> {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<M, G> Lod<M, G> {
+    // scenes reject NaN distances, they would end up anywhere in the order
+    pub fn new(mut levels: Vec<LodLevel<M, G>>) -> Lod<M, G> {
+        levels.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap_or(Ordering::Equal)
+        });
+        Lod { levels }
+    }
+
+    pub fn level(&self, distance: f32) -> Option<&LodLevel<M, G>> {
+        self.levels
+            .iter()
+            .rev()
+            .find(|level| distance >= level.distance)
+            .or_else(|| self.levels.first())
+    }
+}
+
+// the camera the scene is drawn from, amethyst uses the first one
+fn active_camera<'a>(
+    cameras: &ReadStorage<'a, Camera>,
+    transforms: &ReadStorage<'a, GlobalTransform>,
+) -> Option<(Matrix4<f32>, Vector3<f32>)> {
+    (cameras, transforms)
+        .join()
+        .next()
+        .map(|(camera, transform)| {
+            let view = transform.0.try_inverse().unwrap_or_else(Matrix4::identity);
+            (camera.proj * view, transform.0.column(3).xyz())
+        })
+}
+
+// Picks the level of detail of every Lod entity
+#[derive(Default)]
+pub struct LodSystem;
+
+impl<'a> System<'a> for LodSystem {
+    type SystemData = (
+        Read<'a, Settings>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, GlobalTransform>,
+        ReadStorage<'a, Lod>,
+        WriteStorage<'a, Handle<Mesh>>,
+        WriteStorage<'a, Handle<MeshGeometry>>,
+    );
+
+    fn run(
+        &mut self,
+        (settings, cameras, transforms, lods, mut meshes, mut geometries): Self::SystemData,
+    ) {
+        let camera_position = match active_camera(&cameras, &transforms) {
+            Some((_, position)) => position,
+            None => return,
+        };
+        let bias = settings.graphics.lod_bias.max(0.01);
+
+        for (lod, transform, mesh, geometry) in
+            (&lods, &transforms, &mut meshes, &mut geometries).join()
+        {
+            let distance = (transform.0.column(3).xyz() - camera_position).norm() / bias;
+            if let Some(level) = lod.level(distance) {
+                if *mesh != level.mesh {
+                    *mesh = level.mesh.clone();
+                    *geometry = level.geometry.clone();
+                }
+            }
+        }
+    }
+}
+
+fn is_visible(
+    frustum: &Frustum,
+    camera_position: &Vector3<f32>,
+    draw_distance: f32,
+    local_sphere: &BoundingSphere,
+    local_box: &BoundingBox,
+    matrix: &Matrix4<f32>,
+) -> bool {
+    let sphere = local_sphere.transformed(matrix);
+    if (sphere.center.coords - camera_position).norm() - sphere.radius > draw_distance {
+        return false;
+    }
+
+    // the sphere is cheaper, the box is tighter
+    frustum.intersects_sphere(&sphere) && frustum.intersects_box(&local_box.transformed(matrix))
+}
+
+// Hides the meshes outside of the camera's view or further away than the draw distance.
+// Meshes without a MeshGeometry are always drawn, their bounds are unknown
+#[derive(Default)]
+pub struct CullingSystem;
+
+impl<'a> System<'a> for CullingSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Settings>,
+        Read<'a, AssetStorage<MeshGeometry>>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, GlobalTransform>,
+        ReadStorage<'a, Handle<Mesh>>,
+        ReadStorage<'a, Handle<MeshGeometry>>,
+        WriteStorage<'a, Culled>,
+        WriteStorage<'a, Hidden>,
+        Write<'a, DebugStats>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            settings,
+            geometry_storage,
+            cameras,
+            transforms,
+            meshes,
+            geometries,
+            mut culled,
+            mut hidden,
+            mut stats,
+        ): Self::SystemData,
+    ) {
+        let graphics = &settings.graphics;
+        let camera = if graphics.culling {
+            active_camera(&cameras, &transforms)
+        } else {
+            None
+        };
+        let camera = camera
+            .map(|(view_projection, position)| (Frustum::from_matrix(&view_projection), position));
+
+        stats.meshes_drawn = 0;
+        stats.meshes_culled = 0;
+        for (entity, _, transform) in (&*entities, &meshes, &transforms).join() {
+            let geometry = geometries
+                .get(entity)
+                .and_then(|handle| geometry_storage.get(handle));
+            let visible = match (&camera, geometry) {
+                (Some((frustum, position)), Some(geometry)) => is_visible(
+                    frustum,
+                    position,
+                    graphics.draw_distance,
+                    &geometry.bounding_sphere,
+                    &geometry.bounding_box,
+                    &transform.0,
+                ),
+                _ => true,
+            };
+
+            if visible {
+                if culled.remove(entity).is_some() {
+                    hidden.remove(entity);
+                }
+                if !hidden.contains(entity) {
+                    stats.meshes_drawn += 1;
+                }
+            } else {
+                if !hidden.contains(entity) {
+                    hidden.insert(entity, Hidden).unwrap();
+                    culled.insert(entity, Culled).unwrap();
+                }
+                if culled.contains(entity) {
+                    stats.meshes_culled += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(distance: f32, mesh: &'static str) -> LodLevel<&'static str, ()> {
+        LodLevel {
+            distance,
+            mesh,
+            geometry: (),
+        }
+    }
+
+    fn lod() -> Lod<&'static str, ()> {
+        Lod::new(vec![
+            level(20.0, "low"),
+            level(0.0, "high"),
+            level(10.0, "medium"),
+        ])
+    }
+
+    #[test]
+    fn level_below_first() {
+        assert_eq!(lod().level(-1.0).unwrap().mesh, "high");
+        assert_eq!(lod().level(5.0).unwrap().mesh, "high");
+    }
+
+    #[test]
+    fn level_on_distance() {
+        assert_eq!(lod().level(0.0).unwrap().mesh, "high");
+        assert_eq!(lod().level(10.0).unwrap().mesh, "medium");
+        assert_eq!(lod().level(20.0).unwrap().mesh, "low");
+    }
+
+    #[test]
+    fn level_past_last() {
+        assert_eq!(lod().level(1000.0).unwrap().mesh, "low");
+    }
+
+    #[test]
+    fn level_without_levels() {
+        assert!(Lod::<&str, ()>::new(Vec::new()).level(1.0).is_none());
+    }
+
+    #[test]
+    fn level_never_picks_nan_by_distance() {
+        let lod = Lod::new(vec![level(std::f32::NAN, "nan"), level(0.0, "high")]);
+        assert_eq!(lod.level(5.0).unwrap().mesh, "high");
+        assert_eq!(lod.level(1000.0).unwrap().mesh, "high");
+    }
+}
@@ -0,0 +1,363 @@
+use super::{lighting::AmbientLight, CurrentState};
+use crate::logger::prelude::*;
+use amethyst::{
+    core::{shrev::EventChannel, specs::prelude::*, timing::Time},
+    input::InputEvent,
+    renderer::{AmbientColor, Light, Rgba, SkyboxColor},
+};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+const HOURS_PER_DAY: f32 = 24.0;
+// how far the time_forward and time_back actions move the clock
+const SKIPPED_HOURS: f32 = 1.0;
+
+// marks the directional light that follows the sun and the moon
+#[derive(Default)]
+pub struct Sun;
+
+impl Component for Sun {
+    type Storage = NullStorage<Self>;
+}
+
+// used as a resource, serializable so it can be stored in save data
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorldTime {
+    pub day: u32,
+    pub hour: f32,
+    pub rate: f32,
+}
+
+impl WorldTime {
+    pub fn new(hour: f32) -> Self {
+        let mut time = WorldTime {
+            day: 0,
+            hour: 0.0,
+            rate: 1.0,
+        };
+        time.set_hour(hour);
+
+        time
+    }
+
+    pub fn set_hour(&mut self, hour: f32) {
+        self.hour = hour.rem_euclid(HOURS_PER_DAY);
+    }
+
+    // advances by the hours times the rate
+    pub fn advance(&mut self, hours: f32) {
+        self.advance_by(hours * self.rate);
+    }
+
+    // advances by the hours, whatever the rate, going back before day 0 stays on day 0
+    pub fn advance_by(&mut self, hours: f32) {
+        let total = self.hour + hours;
+        let days = (total / HOURS_PER_DAY).floor() as i64;
+        self.day = (i64::from(self.day) + days)
+            .max(0)
+            .min(i64::from(u32::max_value())) as u32;
+        self.set_hour(total);
+    }
+
+    // 0.0 at midnight, 0.5 at noon
+    pub fn day_fraction(&self) -> f32 {
+        self.hour / HOURS_PER_DAY
+    }
+}
+
+impl Default for WorldTime {
+    fn default() -> Self {
+        WorldTime::new(12.0)
+    }
+}
+
+// color keys sampled by hour, wrapping around midnight
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Gradient(pub Vec<(f32, [f32; 4])>);
+
+impl Gradient {
+    pub fn sample(&self, hour: f32) -> [f32; 4] {
+        let keys = &self.0;
+        match keys.len() {
+            0 => [0.0, 0.0, 0.0, 1.0],
+            1 => keys[0].1,
+            len => {
+                let next = keys.iter().position(|&(key, _)| key > hour).unwrap_or(0);
+                let prev = (next + len - 1) % len;
+                let (prev_hour, prev_color) = keys[prev];
+                let (next_hour, next_color) = keys[next];
+
+                let span = (next_hour - prev_hour).rem_euclid(HOURS_PER_DAY);
+                let t = if span > 0.0 {
+                    (hour - prev_hour).rem_euclid(HOURS_PER_DAY) / span
+                } else {
+                    0.0
+                };
+
+                let mut color = [0.0; 4];
+                for (i, channel) in color.iter_mut().enumerate() {
+                    *channel = prev_color[i] + (next_color[i] - prev_color[i]) * t;
+                }
+                color
+            }
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayNightConfig {
+    // real seconds for one full in-game day
+    pub day_length: f32,
+    pub start_hour: f32,
+    pub sky_zenith: Gradient,
+    pub sky_nadir: Gradient,
+    pub sun_color: Gradient,
+    pub sun_intensity: f32,
+    pub moon_color: Gradient,
+    pub moon_intensity: f32,
+    pub ambient_color: Gradient,
+}
+
+impl Default for DayNightConfig {
+    fn default() -> Self {
+        DayNightConfig {
+            day_length: 600.0,
+            start_hour: 12.0,
+            sky_zenith: Gradient(vec![(12.0, [0.3, 0.5, 0.9, 1.0])]),
+            sky_nadir: Gradient(vec![(12.0, [0.7, 0.8, 1.0, 1.0])]),
+            sun_color: Gradient(vec![(12.0, [1.0, 1.0, 1.0, 1.0])]),
+            sun_intensity: 1.0,
+            moon_color: Gradient(vec![(0.0, [0.2, 0.2, 0.3, 1.0])]),
+            moon_intensity: 0.3,
+            ambient_color: Gradient(vec![(12.0, [0.1, 0.1, 0.1, 1.0])]),
+        }
+    }
+}
+
+// Advances the WorldTime and drives the sun, the ambient light and the sky from it.
+// The sky is only visible with the skybox enabled in the graphics settings.
+// The time_forward, time_back and time_pause actions change the time while playing
+pub struct DayNightCycle {
+    config: DayNightConfig,
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+    // the rate to continue with, while the time is paused
+    paused_rate: Option<f32>,
+}
+
+impl DayNightCycle {
+    pub fn new(config: DayNightConfig) -> Self {
+        DayNightCycle {
+            config,
+            event_reader: None,
+            paused_rate: None,
+        }
+    }
+
+    fn control(&mut self, action: &str, world_time: &mut WorldTime) {
+        match action {
+            "time_forward" => world_time.advance_by(SKIPPED_HOURS),
+            "time_back" => world_time.advance_by(-SKIPPED_HOURS),
+            "time_pause" => match self.paused_rate.take() {
+                Some(rate) => world_time.rate = rate,
+                None => {
+                    self.paused_rate = Some(world_time.rate);
+                    world_time.rate = 0.0;
+                }
+            },
+            _ => return,
+        }
+
+        info!(
+            "Time of day is {:.1}, day {}",
+            world_time.hour, world_time.day
+        );
+    }
+}
+
+impl<'a> System<'a> for DayNightCycle {
+    type SystemData = (
+        ReadExpect<'a, CurrentState>,
+        Read<'a, EventChannel<InputEvent<String>>>,
+        Read<'a, Time>,
+        Write<'a, WorldTime>,
+        Write<'a, SkyboxColor>,
+        Write<'a, AmbientColor>,
+        Read<'a, AmbientLight>,
+        ReadStorage<'a, Sun>,
+        WriteStorage<'a, Light>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(
+        &mut self,
+        (
+            cur_state,
+            events,
+            time,
+            mut world_time,
+            mut skybox,
+            mut ambient,
+            ambient_light,
+            suns,
+            mut lights,
+        ): Self::SystemData,
+    ) {
+        let actions: Vec<String> = events
+            .read(self.event_reader.as_mut().unwrap())
+            .filter_map(|event| match event {
+                InputEvent::ActionPressed(action) => Some(action.clone()),
+                _ => None,
+            })
+            .collect();
+        if *cur_state == CurrentState::Loading {
+            return;
+        }
+        if *cur_state == CurrentState::Gameplay {
+            for action in &actions {
+                self.control(action, &mut *world_time);
+            }
+        }
+
+        if self.config.day_length > 0.0 {
+            world_time.advance(time.delta_seconds() / self.config.day_length * HOURS_PER_DAY);
+        }
+
+        let hour = world_time.hour;
+        skybox.zenith = rgba(self.config.sky_zenith.sample(hour));
+        skybox.nadir = rgba(self.config.sky_nadir.sample(hour));
+        // the scenes' own ambient light comes on top
+        let mut ambient_color = self.config.ambient_color.sample(hour);
+        for (channel, value) in ambient_color.iter_mut().zip(&ambient_light.color).take(3) {
+            *channel += value;
+        }
+        ambient.0 = rgba(ambient_color);
+
+        // the sun rises at 6:00 and sets at 18:00, the moon is always opposite of it
+        let angle = world_time.day_fraction() * 2.0 * PI - PI / 2.0;
+        let sun_position = [angle.cos(), angle.sin(), 0.3];
+        let sun_up = sun_position[1] >= 0.0;
+        // fades the light out towards the horizon
+        let elevation = sun_position[1].abs();
+
+        for (light, _) in (&mut lights, &suns).join() {
+            if let Light::Directional(light) = light {
+                if sun_up {
+                    let intensity = self.config.sun_intensity * elevation;
+                    light.color = scaled(self.config.sun_color.sample(hour), intensity);
+                    light.direction = [-sun_position[0], -sun_position[1], -sun_position[2]];
+                } else {
+                    let intensity = self.config.moon_intensity * elevation;
+                    light.color = scaled(self.config.moon_color.sample(hour), intensity);
+                    light.direction = sun_position;
+                }
+            }
+        }
+    }
+}
+
+fn rgba(color: [f32; 4]) -> Rgba {
+    Rgba(color[0], color[1], color[2], color[3])
+}
+
+fn scaled(color: [f32; 4], intensity: f32) -> Rgba {
+    Rgba(
+        color[0] * intensity,
+        color[1] * intensity,
+        color[2] * intensity,
+        color[3],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: [f32; 4], b: [f32; 4]) -> bool {
+        a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1.0e-5)
+    }
+
+    fn gradient() -> Gradient {
+        Gradient(vec![
+            (6.0, [1.0, 0.0, 0.0, 1.0]),
+            (12.0, [0.0, 1.0, 0.0, 1.0]),
+            (20.0, [0.0, 0.0, 1.0, 1.0]),
+        ])
+    }
+
+    #[test]
+    fn sample_between_keys() {
+        assert!(approx(gradient().sample(9.0), [0.5, 0.5, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn sample_exact_key() {
+        assert!(approx(gradient().sample(6.0), [1.0, 0.0, 0.0, 1.0]));
+        assert!(approx(gradient().sample(12.0), [0.0, 1.0, 0.0, 1.0]));
+        assert!(approx(gradient().sample(20.0), [0.0, 0.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn sample_wraps_around_midnight() {
+        // 10 hours from the last key at 20:00 to the first at 6:00
+        assert!(approx(gradient().sample(22.0), [0.2, 0.0, 0.8, 1.0]));
+        assert!(approx(gradient().sample(1.0), [0.5, 0.0, 0.5, 1.0]));
+        assert!(approx(gradient().sample(0.0), [0.4, 0.0, 0.6, 1.0]));
+    }
+
+    #[test]
+    fn sample_single_key() {
+        let gradient = Gradient(vec![(12.0, [0.1, 0.2, 0.3, 1.0])]);
+        assert_eq!(gradient.sample(0.0), [0.1, 0.2, 0.3, 1.0]);
+        assert_eq!(gradient.sample(18.0), [0.1, 0.2, 0.3, 1.0]);
+    }
+
+    #[test]
+    fn advance_within_day() {
+        let mut time = WorldTime::new(10.0);
+        time.advance(2.5);
+        assert_eq!((time.day, time.hour), (0, 12.5));
+    }
+
+    #[test]
+    fn advance_past_midnight() {
+        let mut time = WorldTime::new(23.0);
+        time.advance(2.0);
+        assert_eq!((time.day, time.hour), (1, 1.0));
+
+        time.advance(48.0);
+        assert_eq!((time.day, time.hour), (3, 1.0));
+    }
+
+    #[test]
+    fn advance_back_past_midnight() {
+        let mut time = WorldTime::new(1.0);
+        time.day = 2;
+        time.advance_by(-2.0);
+        assert_eq!((time.day, time.hour), (1, 23.0));
+
+        time.advance_by(-48.0);
+        assert_eq!((time.day, time.hour), (0, 23.0));
+
+        time.advance_by(-24.0);
+        assert_eq!((time.day, time.hour), (0, 23.0));
+    }
+
+    #[test]
+    fn advance_with_rate() {
+        let mut time = WorldTime::new(12.0);
+        time.rate = 0.0;
+        time.advance(5.0);
+        assert_eq!((time.day, time.hour), (0, 12.0));
+
+        time.advance_by(13.0);
+        assert_eq!((time.day, time.hour), (1, 1.0));
+    }
+}
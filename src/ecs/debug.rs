@@ -0,0 +1,196 @@
+use super::{hot_reload::ChangedAssets, CurrentState};
+use crate::{assets::AssetCache, logger::prelude::*, ui::CustomUi};
+use amethyst::{
+    assets::ProgressCounter,
+    audio::AudioFormat,
+    core::{
+        nalgebra::Vector3, shrev::EventChannel, specs::prelude::*, timing::Time, GlobalTransform,
+    },
+    input::InputEvent,
+    renderer::{Camera, Hidden, TextureFormat},
+    ui::{FontFormat, UiCreator, UiText},
+};
+use std::collections::VecDeque;
+
+// the frame times are taken from this many seconds
+const SAMPLE_WINDOW: f32 = 1.0;
+
+// used as a resource, what the debug overlay shows
+#[derive(Default)]
+pub struct DebugStats {
+    pub fps: f32,
+    // in milliseconds
+    pub frame_time_min: f32,
+    pub frame_time_avg: f32,
+    pub frame_time_max: f32,
+    pub entities: usize,
+    pub state: String,
+    pub camera_position: Option<Vector3<f32>>,
+    pub camera_facing: Option<Vector3<f32>>,
+    pub cached_assets: usize,
+    pub meshes_drawn: usize,
+    pub meshes_culled: usize,
+}
+
+impl DebugStats {
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("{:.0} FPS", self.fps),
+            format!(
+                "Frame {:.2} / {:.2} / {:.2} ms (min / avg / max)",
+                self.frame_time_min, self.frame_time_avg, self.frame_time_max
+            ),
+            format!("Entities: {}", self.entities),
+            format!("State: {}", self.state),
+            format!("Cached assets: {}", self.cached_assets),
+            format!(
+                "Meshes: {} drawn, {} culled",
+                self.meshes_drawn, self.meshes_culled
+            ),
+        ];
+
+        if let (Some(position), Some(facing)) = (self.camera_position, self.camera_facing) {
+            let yaw = (-facing.x).atan2(-facing.z).to_degrees();
+            let pitch = facing.y.max(-1.0).min(1.0).asin().to_degrees();
+            lines.push(format!(
+                "Camera: {:.2} {:.2} {:.2}",
+                position.x, position.y, position.z
+            ));
+            lines.push(format!("Facing: yaw {:.1}, pitch {:.1}", yaw, pitch));
+        }
+
+        lines
+    }
+}
+
+// Collects the DebugStats every frame and writes them to the log every few seconds if enabled
+pub struct DebugStatsSystem {
+    frame_times: VecDeque<f32>,
+    log_interval: Option<f32>,
+    since_log: f32,
+}
+
+impl DebugStatsSystem {
+    pub fn new(log_interval: Option<f32>) -> Self {
+        DebugStatsSystem {
+            frame_times: VecDeque::new(),
+            log_interval,
+            since_log: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for DebugStatsSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadExpect<'a, CurrentState>,
+        ReadExpect<'a, AssetCache>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, GlobalTransform>,
+        Write<'a, DebugStats>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, time, cur_state, cache, cameras, transforms, mut stats): Self::SystemData,
+    ) {
+        let delta = time.delta_real_seconds();
+        self.frame_times.push_back(delta);
+        while self.frame_times.iter().sum::<f32>() > SAMPLE_WINDOW && self.frame_times.len() > 1 {
+            self.frame_times.pop_front();
+        }
+
+        let total: f32 = self.frame_times.iter().sum();
+        let count = self.frame_times.len() as f32;
+        stats.fps = if total > 0.0 { count / total } else { 0.0 };
+        stats.frame_time_avg = total / count * 1000.0;
+        stats.frame_time_min = self.frame_times.iter().cloned().fold(delta, f32::min) * 1000.0;
+        stats.frame_time_max = self.frame_times.iter().cloned().fold(delta, f32::max) * 1000.0;
+
+        stats.entities = (&*entities).join().count();
+        stats.state = format!("{:?}", *cur_state);
+        stats.cached_assets = cache.len();
+
+        // the camera looks along its negative z axis
+        let camera = (&cameras, &transforms).join().next();
+        stats.camera_position = camera.map(|(_, transform)| transform.0.column(3).xyz());
+        stats.camera_facing = camera.map(|(_, transform)| -transform.0.column(2).xyz().normalize());
+
+        if let Some(log_interval) = self.log_interval {
+            self.since_log += delta;
+            if self.since_log >= log_interval {
+                self.since_log = 0.0;
+                info!("{}", stats.lines().join(", "));
+            }
+        }
+    }
+}
+
+// Shows the DebugStats on top of everything, toggled with the debug_overlay action
+#[derive(Default)]
+pub struct DebugOverlay {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+    overlay: Option<Entity>,
+    visible: bool,
+}
+
+impl<'a> System<'a> for DebugOverlay {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EventChannel<InputEvent<String>>>,
+        Read<'a, DebugStats>,
+        Write<'a, ChangedAssets>,
+        UiCreator<'a, AudioFormat, TextureFormat, FontFormat, CustomUi>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, Hidden>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(
+        &mut self,
+        (entities, events, stats, mut changed_assets, mut ui_creator, mut texts, mut hidden): Self::SystemData,
+    ) {
+        for event in events.read(self.event_reader.as_mut().unwrap()) {
+            if let InputEvent::ActionPressed(action) = event {
+                if action == "debug_overlay" {
+                    self.visible = !self.visible;
+                }
+            }
+        }
+
+        // recreated from the changed prefab during development
+        if self.overlay.is_some() && changed_assets.take("ui/debug.ron") {
+            entities
+                .delete(self.overlay.take().unwrap())
+                .unwrap_log("Failed to delete the debug overlay");
+        }
+
+        // only created the first time it's shown
+        let overlay = match self.overlay {
+            Some(overlay) => overlay,
+            None if self.visible => {
+                let overlay = ui_creator.create("ui/debug.ron", &mut ProgressCounter::new());
+                self.overlay = Some(overlay);
+                overlay
+            }
+            None => return,
+        };
+
+        if self.visible {
+            hidden.remove(overlay);
+            if let Some(text) = texts.get_mut(overlay) {
+                text.text = stats.lines().join("\n");
+            }
+        } else if !hidden.contains(overlay) {
+            hidden.insert(overlay, Hidden).unwrap();
+        }
+    }
+}
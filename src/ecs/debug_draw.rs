@@ -0,0 +1,66 @@
+use crate::{assets::MeshGeometry, settings::Settings};
+use amethyst::{
+    assets::{AssetStorage, Handle},
+    core::{nalgebra::Point3, specs::prelude::*, GlobalTransform},
+    renderer::{DebugLines, Rgba},
+};
+
+const WIREFRAME_COLOR: Rgba = Rgba(0.0, 1.0, 0.0, 1.0);
+const NORMAL_COLOR: Rgba = Rgba(0.2, 0.4, 1.0, 1.0);
+
+// Draws the triangles and normals of the meshes as debug lines when the graphics
+// settings ask for them. The lines are drawn by the DrawDebugLines pass and cleared after
+#[derive(Default)]
+pub struct DebugDraw;
+
+impl<'a> System<'a> for DebugDraw {
+    type SystemData = (
+        Read<'a, Settings>,
+        Read<'a, AssetStorage<MeshGeometry>>,
+        ReadStorage<'a, Handle<MeshGeometry>>,
+        ReadStorage<'a, GlobalTransform>,
+        Write<'a, DebugLines>,
+    );
+
+    fn run(&mut self, (settings, storage, handles, transforms, mut lines): Self::SystemData) {
+        let graphics = &settings.graphics;
+        if !graphics.wireframe && !graphics.normals {
+            return;
+        }
+
+        for (handle, transform) in (&handles, &transforms).join() {
+            let geometry = match storage.get(handle) {
+                Some(geometry) => geometry,
+                None => continue,
+            };
+            let matrix = transform.0;
+            let positions: Vec<Point3<f32>> = geometry
+                .positions
+                .iter()
+                .map(|position| matrix.transform_point(position))
+                .collect();
+
+            if graphics.wireframe {
+                for triangle in positions.chunks(3) {
+                    for (index, &start) in triangle.iter().enumerate() {
+                        let end = triangle[(index + 1) % triangle.len()];
+                        lines.draw_line(start, end, WIREFRAME_COLOR);
+                    }
+                }
+            }
+
+            // ignores non-uniform scaling, close enough for debugging
+            if graphics.normals {
+                for (&position, normal) in positions.iter().zip(&geometry.normals) {
+                    if let Some(direction) = matrix.transform_vector(normal).try_normalize(1.0e-6) {
+                        lines.draw_line(
+                            position,
+                            position + direction * graphics.normal_length,
+                            NORMAL_COLOR,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
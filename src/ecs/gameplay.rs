@@ -62,11 +62,16 @@ impl<'a> System<'a> for CameraRotation {
 
 pub struct CameraMovement {
     speed: f32,
+    // multiplies the speed while the sprint action is held
+    sprint_factor: f32,
 }
 
 impl CameraMovement {
-    pub fn new(speed: f32) -> Self {
-        CameraMovement { speed }
+    pub fn new(speed: f32, sprint_factor: f32) -> Self {
+        CameraMovement {
+            speed,
+            sprint_factor,
+        }
     }
 }
 
@@ -89,10 +94,16 @@ impl<'a> System<'a> for CameraMovement {
             let walk = input_handler.axis_value("walk").unwrap() as f32;
             let strafe = input_handler.axis_value("strafe").unwrap() as f32;
 
+            let speed = if input_handler.action_is_down("sprint").unwrap_or(false) {
+                self.speed * self.sprint_factor
+            } else {
+                self.speed
+            };
+
             if focus.is_focused && hide.hide {
                 if let Some(dir) = Unit::try_new(Vector3::new(-strafe, 0.0, -walk), 1.0e-6) {
                     for (transform, _) in (&mut transform, &camera).join() {
-                        transform.move_along_local(dir, time.delta_seconds() * self.speed);
+                        transform.move_along_local(dir, time.delta_seconds() * speed);
                     }
                 }
             }
@@ -0,0 +1,191 @@
+use crate::{
+    assets::{self, AssetFormat},
+    logger::prelude::*,
+    settings::{DisplayMode, Settings, SettingsOverrides},
+};
+use amethyst::{
+    config::Config,
+    core::{specs::prelude::*, timing::Time},
+    input::{Bindings, InputHandler},
+    renderer::{DisplayConfig, WindowMessages},
+};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// used as a resource, paths of the assets that changed on disk and parsed correctly,
+// kept until whatever uses the asset takes them
+#[derive(Default)]
+pub struct ChangedAssets(Vec<String>);
+
+impl ChangedAssets {
+    pub fn push(&mut self, path: String) {
+        if !self.0.contains(&path) {
+            self.0.push(path);
+        }
+    }
+
+    // true if the asset changed since it was last taken
+    pub fn take(&mut self, path: &str) -> bool {
+        let len = self.0.len();
+        self.0.retain(|changed| changed != path);
+        self.0.len() != len
+    }
+}
+
+// Watches the assets and config folders during development and reloads what changed.
+// Meshes and textures are reloaded by amethyst's HotReloadBundle, this system
+// applies the config files and tells states which assets changed.
+// Without an assets folder, when the assets are packed, only the config files are watched
+pub struct HotReload {
+    assets_dir: Option<PathBuf>,
+    config_dir: PathBuf,
+    interval: f32,
+    elapsed: f32,
+    modified: HashMap<PathBuf, SystemTime>,
+}
+
+impl HotReload {
+    pub fn new(assets_dir: Option<PathBuf>, config_dir: PathBuf, interval: f32) -> Self {
+        let mut hot_reload = HotReload {
+            assets_dir,
+            config_dir,
+            interval,
+            elapsed: 0.0,
+            modified: HashMap::new(),
+        };
+        hot_reload.changed_files();
+
+        hot_reload
+    }
+
+    // files that were modified or created since the last call
+    fn changed_files(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+
+        for dir in self.assets_dir.iter().chain(Some(&self.config_dir)) {
+            let files = match assets::list_files(dir) {
+                Ok(files) => files,
+                Err(err) => {
+                    warn!("Failed to watch {}: {}", dir.display(), err);
+                    continue;
+                }
+            };
+
+            for file in files {
+                let path = dir.join(file);
+                if let Ok(modified) = fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+                    if self.modified.insert(path.clone(), modified) != Some(modified) {
+                        changed.push(path);
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+
+    fn reload_asset(assets_dir: &Path, path: &Path) -> Option<String> {
+        let relative = assets::relative_path(assets_dir, path)?;
+        let format = AssetFormat::from_path(&relative)?;
+
+        let result = fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| assets::decode(format, bytes));
+
+        match result {
+            Ok(()) => {
+                info!("Reloaded {}", relative);
+                Some(relative)
+            }
+            Err(err) => {
+                error!("Failed to reload {}: {}", relative, err);
+                None
+            }
+        }
+    }
+}
+
+impl<'a> System<'a> for HotReload {
+    type SystemData = (
+        Read<'a, Time>,
+        Write<'a, ChangedAssets>,
+        Write<'a, InputHandler<String, String>>,
+        Write<'a, WindowMessages>,
+        Write<'a, Settings>,
+        Read<'a, SettingsOverrides>,
+    );
+
+    fn run(
+        &mut self,
+        (time, mut changed_assets, mut input, mut window, mut settings, overrides): Self::SystemData,
+    ) {
+        self.elapsed += time.delta_real_seconds();
+        if self.elapsed < self.interval {
+            return;
+        }
+        self.elapsed = 0.0;
+
+        for path in self.changed_files() {
+            if let Some(assets_dir) = self.assets_dir.as_ref().filter(|dir| path.starts_with(dir)) {
+                if let Some(asset) = Self::reload_asset(assets_dir, &path) {
+                    changed_assets.push(asset);
+                }
+                continue;
+            }
+
+            match path.file_name().and_then(|name| name.to_str()) {
+                Some("key_bindings.ron") => {
+                    match Bindings::<String, String>::load_no_fallback(&path) {
+                        Ok(bindings) => {
+                            input.bindings = bindings;
+                            info!("Reloaded key bindings");
+                        }
+                        Err(err) => error!("Failed to reload key bindings: {}", err),
+                    }
+                }
+                Some("display.ron") => match DisplayConfig::load_no_fallback(&path) {
+                    Ok(display) => {
+                        // the window size in display.ron is only used without one in settings.ron
+                        let display_settings = overrides.display(&settings.display);
+                        let resize = display_settings.mode == DisplayMode::Windowed
+                            && display_settings.resolution.is_none();
+                        apply_display(&display, resize, &mut window);
+                        info!("Reloaded display settings, multisampling applies on the next start");
+                    }
+                    Err(err) => error!("Failed to reload display settings: {}", err),
+                },
+                Some("settings.ron") => match Settings::load_no_fallback(&path) {
+                    Ok(reloaded) => {
+                        *settings = Settings {
+                            path: path.clone(),
+                            ..reloaded
+                        };
+                        info!("Reloaded settings");
+                    }
+                    Err(err) => error!("Failed to reload settings: {}", err),
+                },
+                _ => (),
+            }
+        }
+    }
+}
+
+// only the settings that can change without recreating the window, the display mode
+// is applied from settings.ron by the DisplaySystem
+fn apply_display(display: &DisplayConfig, resize: bool, window: &mut WindowMessages) {
+    let dimensions = if resize { display.dimensions } else { None };
+    let min_dimensions = display.min_dimensions;
+    let max_dimensions = display.max_dimensions;
+
+    window.send_command(move |window| {
+        window.set_min_dimensions(min_dimensions.map(Into::into));
+        window.set_max_dimensions(max_dimensions.map(Into::into));
+        if let Some(dimensions) = dimensions {
+            window.set_inner_size(dimensions.into());
+        }
+    });
+}
@@ -0,0 +1,38 @@
+use amethyst::core::specs::prelude::*;
+
+// used as a resource, the light reaching every surface from all directions,
+// added to the ambient color of the day/night cycle
+#[derive(Clone, Default)]
+pub struct AmbientLight {
+    // already multiplied by the intensity
+    pub color: [f32; 4],
+}
+
+// a scene's contribution to the AmbientLight, gone when the scene is deleted
+#[derive(Clone)]
+pub struct Ambient {
+    pub color: [f32; 4],
+    pub intensity: f32,
+}
+
+impl Component for Ambient {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// Sums the Ambient components of the loaded scenes into the AmbientLight
+#[derive(Default)]
+pub struct AmbientLightSystem;
+
+impl<'a> System<'a> for AmbientLightSystem {
+    type SystemData = (Write<'a, AmbientLight>, ReadStorage<'a, Ambient>);
+
+    fn run(&mut self, (mut ambient_light, ambients): Self::SystemData) {
+        let mut color = [0.0, 0.0, 0.0, 1.0];
+        for ambient in ambients.join() {
+            for (channel, value) in color.iter_mut().zip(&ambient.color).take(3) {
+                *channel += value * ambient.intensity;
+            }
+        }
+        ambient_light.color = color;
+    }
+}
@@ -1,7 +1,8 @@
 use super::CurrentState;
 use amethyst::core::{specs::prelude::*, timing::Time, Transform};
+use serde::{Deserialize, Serialize};
 
-#[derive(Default)]
+#[derive(Clone, Default, Deserialize, Serialize)]
 pub struct Rotates;
 
 impl Component for Rotates {
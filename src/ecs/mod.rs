@@ -1,11 +1,18 @@
 pub mod camera;
+pub mod culling;
+pub mod daynight;
+pub mod debug;
+pub mod debug_draw;
 pub mod gameplay;
+pub mod hot_reload;
+pub mod lighting;
 pub mod mainmenu;
 
 // used as a resource for systems
-#[derive(PartialEq)]
+#[derive(Debug, PartialEq)]
 pub enum CurrentState {
     Loading,
     MainMenu,
     Gameplay,
+    Benchmark,
 }
@@ -0,0 +1,65 @@
+use crate::settings::{LimitStrategy, Settings};
+use amethyst::{
+    controls::WindowFocus,
+    core::{
+        frame_limiter::{FrameLimiter, FrameRateLimitStrategy},
+        specs::prelude::*,
+    },
+};
+use std::time::Duration;
+
+// offered in the settings screen, None is unlimited
+pub const FPS_OPTIONS: &[Option<u32>] =
+    &[None, Some(30), Some(60), Some(120), Some(144), Some(240)];
+// the SleepAndYield strategy yields for this long before each frame
+const YIELD_MILLIS: u64 = 2;
+// FrameLimiter divides by the frame rate even when unlimited, so it can't be zero
+const UNLIMITED_FPS: u32 = 144;
+
+// Replaces the application's FrameLimiter whenever the frame rate settings or the
+// window focus change
+#[derive(Default)]
+pub struct FrameRateSystem {
+    applied: Option<(Option<u32>, LimitStrategy)>,
+}
+
+impl<'a> System<'a> for FrameRateSystem {
+    type SystemData = (
+        Read<'a, Settings>,
+        Read<'a, WindowFocus>,
+        Write<'a, FrameLimiter>,
+    );
+
+    fn run(&mut self, (settings, focus, mut limiter): Self::SystemData) {
+        let frame_rate = &settings.frame_rate;
+        let fps = if focus.is_focused {
+            frame_rate.max_fps
+        } else {
+            // never faster than when focused
+            match (frame_rate.unfocused_fps, frame_rate.max_fps) {
+                (Some(unfocused), Some(max)) => Some(unfocused.min(max)),
+                (unfocused, max) => unfocused.or(max),
+            }
+        };
+
+        let applied = Some((fps, frame_rate.strategy));
+        if self.applied == applied {
+            return;
+        }
+        self.applied = applied;
+
+        *limiter = match fps {
+            Some(fps) if fps > 0 => {
+                let strategy = match frame_rate.strategy {
+                    LimitStrategy::Yield => FrameRateLimitStrategy::Yield,
+                    LimitStrategy::Sleep => FrameRateLimitStrategy::Sleep,
+                    LimitStrategy::SleepAndYield => {
+                        FrameRateLimitStrategy::SleepAndYield(Duration::from_millis(YIELD_MILLIS))
+                    }
+                };
+                FrameLimiter::new(strategy, fps)
+            }
+            _ => FrameLimiter::new(FrameRateLimitStrategy::Unlimited, UNLIMITED_FPS),
+        };
+    }
+}
@@ -1,13 +1,19 @@
+use crate::{
+    logger::prelude::*,
+    screenshot::DrawScreenshot,
+    settings::{CameraSettings, DisplayMode, Projection, Settings, SettingsOverrides, Shading},
+};
 use amethyst::{
-    core::{
-        nalgebra::{Perspective3, Quaternion, Translation3, UnitQuaternion, Vector3},
-        specs::prelude::*,
-        Transform,
-    },
+    core::nalgebra::{Matrix4, Orthographic3, Perspective3},
     prelude::*,
     renderer::{
-        Camera, DisplayConfig, DrawShaded, Light, Pipeline, PointLight, PosNormTex, RenderBundle,
-        Rgba, ScreenDimensions, Stage,
+        error::Result as RenderResult,
+        pipe::{
+            pass::{Pass, PassData},
+            Effect, NewEffect,
+        },
+        Camera, DisplayConfig, DrawDebugLines, DrawFlat, DrawPbm, DrawShaded, DrawSkybox, Encoder,
+        Factory, Pipeline, PosColorNorm, PosNormTangTex, PosNormTex, RenderBundle, Stage,
     },
     ui::DrawUi,
 };
@@ -17,48 +23,130 @@ use std::path::PathBuf;
 const INIT_WIDTH: u32 = 1280;
 const INIT_HEIGHT: u32 = 720;
 
-pub fn initialize_camera(world: &mut World, transform: Transform) -> Entity {
-    let aspect = world.read_resource::<ScreenDimensions>().aspect_ratio();
-    let camera = Camera {
-        proj: Perspective3::new(aspect, 45.0f32.to_radians(), 0.1, 2000.0).to_homogeneous(),
-    };
+pub fn camera(aspect: f32, settings: &CameraSettings) -> Camera {
+    Camera {
+        proj: projection(aspect, settings.fov, settings),
+    }
+}
+
+// the field of view is passed separately, as it changes while sprinting and zooming
+pub fn projection(aspect: f32, fov: f32, settings: &CameraSettings) -> Matrix4<f32> {
+    match settings.projection {
+        Projection::Perspective => {
+            Perspective3::new(aspect, fov.to_radians(), settings.near, settings.far)
+                .to_homogeneous()
+        }
+        Projection::Orthographic => {
+            let height = settings.ortho_size;
+            let width = height * aspect;
+            Orthographic3::new(-width, width, -height, height, settings.near, settings.far)
+                .to_homogeneous()
+        }
+    }
+}
+
+// never drawn, for passes that only need an effect
+const PLACEHOLDER_VERT_SRC: &[u8] = b"
+#version 150 core
+void main() {
+    gl_Position = vec4(0.0);
+}
+";
+const PLACEHOLDER_FRAG_SRC: &[u8] = b"
+#version 150 core
+out vec4 color;
+void main() {
+    color = vec4(0.0);
+}
+";
 
-    world.create_entity().with(transform).with(camera).build()
+pub fn compile_placeholder(effect: NewEffect) -> RenderResult<Effect> {
+    effect
+        .simple(PLACEHOLDER_VERT_SRC, PLACEHOLDER_FRAG_SRC)
+        .with_output("color", None)
+        .build()
 }
 
-pub fn initialize_light(world: &mut World) -> Entity {
-    let light: Light = PointLight {
-        intensity: 10.0,
-        color: Rgba::white(),
-        radius: 5.0,
-        ..Default::default()
+// A pass that can be left out when the pipeline is built. The pipeline's type depends
+// on its passes, this keeps it the same whichever passes the settings select
+pub struct Optional<P>(Option<P>);
+
+impl<P> Optional<P> {
+    pub fn new(pass: P, enabled: bool) -> Self {
+        Optional(if enabled { Some(pass) } else { None })
     }
-    .into();
+}
 
-    let transform = Transform::new(
-        Translation3::new(2.0, 2.0, -2.0),
-        UnitQuaternion::from_quaternion(Quaternion::new(0.0, 0.0, 1.0, 0.0)),
-        Vector3::new(1.0, 1.0, 1.0),
-    );
+impl<'a, P> PassData<'a> for Optional<P>
+where
+    P: PassData<'a>,
+{
+    type Data = P::Data;
+}
 
-    world.create_entity().with(light).with(transform).build()
+impl<P: Pass> Pass for Optional<P> {
+    fn compile(&mut self, effect: NewEffect) -> RenderResult<Effect> {
+        match &mut self.0 {
+            Some(pass) => pass.compile(effect),
+            None => compile_placeholder(effect),
+        }
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        factory: Factory,
+        data: <Self as PassData<'a>>::Data,
+    ) {
+        if let Some(pass) = &mut self.0 {
+            pass.apply(encoder, effect, factory, data);
+        }
+    }
 }
 
 pub fn add_renderer<'a, 'b>(
     game_data_builder: GameDataBuilder<'a, 'b>,
     resources_path: &PathBuf,
+    settings: &Settings,
+    overrides: &SettingsOverrides,
 ) -> amethyst::Result<GameDataBuilder<'a, 'b>> {
     let mut display_config = DisplayConfig::load(resources_path);
     display_config.title = crate::NAME.into();
-    if !display_config.fullscreen {
-        display_config.dimensions = Some((INIT_WIDTH, INIT_HEIGHT));
-    }
 
+    // the DisplaySystem moves the window to the selected monitor once it exists
+    let display = overrides.display(&settings.display);
+    display_config.fullscreen = display.mode == DisplayMode::DesktopFullscreen;
+    display_config.decorations = display.mode == DisplayMode::Windowed;
+    display_config.dimensions = display
+        .resolution
+        .or(display_config.dimensions)
+        .or(Some((INIT_WIDTH, INIT_HEIGHT)));
+    display_config.vsync = settings.frame_rate.vsync;
+
+    let shading = overrides.shading(&settings.graphics);
+    let settings = &settings.graphics;
+    info!("Using {} shading", shading.name());
     let pipeline = Pipeline::build().with_stage(
         Stage::with_backbuffer()
-            .clear_target([0.3, 0.3, 0.3, 1.0], 1.0)
-            .with_pass(DrawShaded::<PosNormTex>::new())
-            .with_pass(DrawUi::new()),
+            .clear_target(settings.clear_color, 1.0)
+            .with_pass(Optional::new(DrawSkybox::new(), settings.skybox))
+            .with_pass(Optional::new(
+                DrawFlat::<PosNormTex>::new(),
+                shading == Shading::Flat,
+            ))
+            .with_pass(Optional::new(
+                DrawShaded::<PosNormTex>::new(),
+                shading == Shading::Shaded,
+            ))
+            .with_pass(Optional::new(
+                DrawPbm::<PosNormTangTex>::new(),
+                shading == Shading::Pbr,
+            ))
+            // the wireframe and normals, drawn by the DebugDraw system
+            .with_pass(DrawDebugLines::<PosColorNorm>::new())
+            .with_pass(DrawUi::new())
+            .with_pass(DrawScreenshot::new()),
     );
 
     game_data_builder.with_bundle(RenderBundle::new(pipeline, Some(display_config)))
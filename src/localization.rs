@@ -0,0 +1,216 @@
+use crate::{
+    assets::{AssetProgress, StringTable},
+    logger::prelude::*,
+    settings::Settings,
+};
+use amethyst::{
+    assets::{AssetStorage, Completion, Handle, Loader, RonFormat},
+    core::specs::prelude::*,
+    ui::UiText,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+// used when a string is missing in the selected language
+pub const FALLBACK_LANGUAGE: &str = "en";
+pub const LANGUAGES: &[&str] = &["en", "de"];
+
+// UI texts starting with this are keys into the string tables
+const KEY_PREFIX: char = '$';
+
+pub fn strings_path(language: &str) -> String {
+    format!("text/{}/strings.ron", language)
+}
+
+// used as a resource, the string tables of the selected language and its fallbacks
+#[derive(Default)]
+pub struct Localization {
+    language: String,
+    tables: Vec<(String, HashMap<String, String>)>,
+    loading: Vec<(String, Handle<StringTable>)>,
+    progress: AssetProgress,
+    // bumped whenever the tables change, so texts are translated again
+    generation: u32,
+    missing: Mutex<HashSet<(String, String)>>,
+}
+
+impl Localization {
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    // the tables of the language finished loading
+    pub fn is_ready(&self, language: &str) -> bool {
+        self.language == language && self.loading.is_empty()
+    }
+
+    // changes whenever other tables are swapped in
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    // tables were requested and aren't swapped in yet
+    fn is_loading(&self) -> bool {
+        !self.loading.is_empty()
+    }
+
+    // starts loading the tables, the current ones are used until they are done
+    pub fn load(&mut self, language: &str, loader: &Loader, storage: &AssetStorage<StringTable>) {
+        info!("Loading language {}", language);
+
+        self.language = language.to_string();
+        self.progress = AssetProgress::new();
+
+        let mut chain = vec![language];
+        if language != FALLBACK_LANGUAGE {
+            chain.push(FALLBACK_LANGUAGE);
+        }
+
+        let progress = &mut self.progress;
+        self.loading = chain
+            .into_iter()
+            .map(|language| {
+                let path = strings_path(language);
+                let handle = loader.load(
+                    path.as_str(),
+                    RonFormat,
+                    (),
+                    progress.named(path.as_str()),
+                    storage,
+                );
+                (language.to_string(), handle)
+            })
+            .collect();
+    }
+
+    // swaps in the tables once they are loaded
+    fn poll(&mut self, storage: &AssetStorage<StringTable>) {
+        match self.progress.complete() {
+            Completion::Loading => return,
+            Completion::Complete => (),
+            Completion::Failed => {
+                for (asset, err) in self.progress.errors() {
+                    error!("Failed to load {}: {}", asset, err);
+                }
+            }
+        }
+
+        // the tables that failed are left out of the chain
+        self.tables = self
+            .loading
+            .drain(..)
+            .filter_map(|(language, handle)| {
+                storage
+                    .get(&handle)
+                    .map(|table| (language, table.0.clone()))
+            })
+            .collect();
+        self.generation += 1;
+    }
+
+    // the string for the key, from the first table that has it
+    pub fn get(&self, key: &str) -> String {
+        for (index, (language, table)) in self.tables.iter().enumerate() {
+            if let Some(string) = table.get(key) {
+                if index > 0 {
+                    self.report_missing(key, &self.tables[0].0, Some(language));
+                }
+                return string.clone();
+            }
+        }
+
+        self.report_missing(key, &self.language, None);
+        key.to_string()
+    }
+
+    // translates the text if it is a key, other texts are returned as they are
+    pub fn text(&self, text: &str) -> String {
+        if text.starts_with(KEY_PREFIX) {
+            self.get(&text[KEY_PREFIX.len_utf8()..])
+        } else {
+            text.to_string()
+        }
+    }
+
+    // logs every missing key only once
+    fn report_missing(&self, key: &str, language: &str, fallback: Option<&str>) {
+        let mut missing = self.missing.lock().unwrap();
+        if !missing.insert((key.to_string(), language.to_string())) {
+            return;
+        }
+
+        match fallback {
+            Some(fallback) => warn!("Missing {} in {}, using {}", key, language, fallback),
+            None => warn!("Missing {} in all languages", key),
+        }
+    }
+}
+
+// the key of a UI text, which is translated again when the language changes
+pub struct Localized(pub String);
+
+impl Component for Localized {
+    type Storage = DenseVecStorage<Self>;
+}
+
+// Loads the string tables of the language in the settings and translates UI texts
+// written as "$key". Texts of widgets are translated by the widget systems
+#[derive(Default)]
+pub struct LocalizationSystem {
+    generation: u32,
+}
+
+impl<'a> System<'a> for LocalizationSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<StringTable>>,
+        Read<'a, Settings>,
+        Write<'a, Localization>,
+        WriteStorage<'a, Localized>,
+        WriteStorage<'a, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            loader,
+            storage,
+            settings,
+            mut localization,
+            mut localized,
+            mut texts,
+        ): Self::SystemData,
+    ) {
+        if localization.language() != settings.language {
+            localization.load(&settings.language, &loader, &storage);
+        }
+        if localization.is_loading() {
+            localization.poll(&storage);
+        }
+        // nothing to translate with until the first language is loaded
+        if localization.tables.is_empty() {
+            return;
+        }
+
+        // new texts are translated when they show up
+        for (entity, text, _) in (&entities, &mut texts, !localized.mask().clone()).join() {
+            if text.text.starts_with(KEY_PREFIX) {
+                localized
+                    .insert(entity, Localized(text.text.clone()))
+                    .unwrap_log("Failed to insert Localized");
+                text.text = localization.text(&text.text);
+            }
+        }
+
+        if self.generation != localization.generation {
+            for (key, text) in (&localized, &mut texts).join() {
+                text.text = localization.text(&key.0);
+            }
+            self.generation = localization.generation;
+        }
+    }
+}
@@ -165,7 +165,7 @@ fn format_hhmmssnnn() -> String {
     )
 }
 
-fn format_yyyymmdd_hhmmss() -> String {
+pub fn format_yyyymmdd_hhmmss() -> String {
     let time = Local::now();
 
     format!(
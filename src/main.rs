@@ -1,26 +1,47 @@
 #![allow(clippy::type_complexity)]
 
+mod assets;
+mod bounds;
+mod display;
 mod ecs;
+mod frame_rate;
 mod graphics;
+mod localization;
 mod logger;
+mod profiler;
+mod scene;
+mod screenshot;
+mod settings;
 mod states;
 mod ui;
 
 use amethyst::{
-    assets::Processor,
+    assets::{HotReloadBundle, Loader, PrefabLoaderSystem, Processor},
     audio::Source,
+    config::Config,
     controls::{CursorHideSystem, HideCursor, MouseFocusUpdateSystem},
     core::TransformBundle,
     input::InputBundle,
     prelude::*,
+    renderer::DebugLines,
     ui::UiBundle,
     utils,
 };
+use assets::{AssetCache, MeshGeometry, PackSource, StringTable, Tips, PACK_FILE};
 use clap::{App, Arg};
-use ecs::CurrentState;
+use ecs::{
+    daynight::{DayNightConfig, WorldTime},
+    hot_reload::{ChangedAssets, HotReload},
+    CurrentState,
+};
 use logger::{prelude::*, Logger, UnwrapLog};
-use states::LoadingState;
-use std::path::PathBuf;
+use profiler::{ProfileExport, Profiler, WithTimed};
+use settings::{Settings, SettingsOverrides, Shading};
+use states::{BenchmarkConfig, LoadingConfig, LoadingState};
+use std::{
+    path::{Path, PathBuf},
+    process,
+};
 
 const NAME: &str = env!("CARGO_PKG_NAME");
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -34,8 +55,78 @@ fn main() {
                 .short("c")
                 .help("Enable console coloring"),
         )
+        .arg(
+            Arg::with_name("dev")
+                .long("dev")
+                .help("Reloads changed assets and config files while running"),
+        )
+        .arg(
+            Arg::with_name("time_of_day")
+                .long("time-of-day")
+                .value_name("HOUR")
+                .help("Overrides the in-game hour the world starts at"),
+        )
+        .arg(
+            Arg::with_name("log_stats")
+                .long("log-stats")
+                .value_name("SECONDS")
+                .help("Writes the debug stats to the log at the given interval"),
+        )
+        .arg(
+            Arg::with_name("profile")
+                .long("profile")
+                .value_name("FILE")
+                .help("Times the systems, writes a Chrome trace or a CSV summary on exit"),
+        )
+        .arg(
+            Arg::with_name("benchmark")
+                .long("benchmark")
+                .value_name("FILE")
+                .help("Flies the camera along the path in the file and writes a frame time report"),
+        )
+        .arg(
+            Arg::with_name("windowed")
+                .long("windowed")
+                .help("Starts in a window, whatever the display mode in the settings"),
+        )
+        .arg(
+            Arg::with_name("resolution")
+                .long("resolution")
+                .value_name("WxH")
+                .help("Overrides the window size from the settings"),
+        )
+        .arg(
+            Arg::with_name("shading")
+                .long("shading")
+                .value_name("MODE")
+                .possible_values(&["flat", "shaded", "pbr"])
+                .help("Overrides the shading pass from the settings"),
+        )
+        .arg(
+            Arg::with_name("validate_assets")
+                .long("validate-assets")
+                .help("Checks the assets and config files without starting the game"),
+        )
+        .arg(
+            Arg::with_name("update_manifest")
+                .long("update-manifest")
+                .help("Regenerates the asset manifest without starting the game"),
+        )
+        .arg(
+            Arg::with_name("build_pack")
+                .long("build-pack")
+                .value_name("FILE")
+                .help("Packs the assets folder into an archive without starting the game"),
+        )
+        .arg(
+            Arg::with_name("uncompressed")
+                .long("uncompressed")
+                .requires("build_pack")
+                .help("Stores the packed assets without compression"),
+        )
         .get_matches();
     let color = clap.is_present("color");
+    let dev = clap.is_present("dev");
 
     Logger::init(color, &["gfx_device_gl"]);
 
@@ -43,51 +134,306 @@ fn main() {
     let assets_path = root_dir.join("assets");
     let config_path = root_dir.join("config");
 
+    if clap.is_present("update_manifest") {
+        assets::update_manifest(&assets_path);
+        return;
+    }
+
+    if let Some(output) = clap.value_of("build_pack") {
+        let compress = !clap.is_present("uncompressed");
+        let count = assets::build_pack(&assets_path, Path::new(output), compress)
+            .unwrap_log("Failed to build asset pack");
+        info!("Packed {} assets into {}", count, output);
+        return;
+    }
+
+    if clap.is_present("validate_assets") {
+        let problems = assets::validate(&assets_path, &config_path);
+        process::exit(if problems == 0 { 0 } else { 1 });
+    }
+
     let key_bindings_path = config_path.join("key_bindings.ron");
     let display_path = config_path.join("display.ron");
+    let day_night_path = config_path.join("day_night.ron");
+    let loading_path = config_path.join("loading.ron");
+    let settings_path = config_path.join("settings.ron");
+
+    let day_night_config = DayNightConfig::load(day_night_path);
+    let start_hour = clap
+        .value_of("time_of_day")
+        .map(|hour| hour.parse::<f32>().unwrap_log("Invalid time of day"))
+        .unwrap_or(day_night_config.start_hour);
+    let log_stats = clap
+        .value_of("log_stats")
+        .map(|interval| interval.parse::<f32>().unwrap_log("Invalid stats interval"));
+    let profile_path = clap.value_of("profile").map(PathBuf::from);
+    let profiler = Profiler::new(profile_path.is_some());
+    let benchmark = clap
+        .value_of("benchmark")
+        .map(|path| BenchmarkConfig::load_no_fallback(path).unwrap_log("Failed to load benchmark"));
+    let loading_config = LoadingConfig::load(loading_path);
+    let settings = Settings::open(settings_path);
+    let overrides = SettingsOverrides {
+        shading: clap.value_of("shading").map(|shading| {
+            *Shading::ALL
+                .iter()
+                .find(|mode| mode.name() == shading)
+                .unwrap()
+        }),
+        windowed: clap.is_present("windowed"),
+        resolution: clap.value_of("resolution").map(|resolution| {
+            parse_resolution(resolution).unwrap_log("Invalid resolution, expected WxH")
+        }),
+    };
+
+    // the packed assets are preferred, the loose assets folder is used during development
+    let pack_path = root_dir.join(PACK_FILE);
+    let pack = if pack_path.is_file() {
+        let pack = PackSource::open(&pack_path).unwrap_log("Failed to open asset pack");
+        info!("Loading {} assets from {}", pack.entry_count(), PACK_FILE);
+        Some(pack)
+    } else {
+        info!("No {} found, loading loose assets", PACK_FILE);
+        None
+    };
 
     let input_bundle = InputBundle::<String, String>::new()
         .with_bindings_from_file(key_bindings_path)
         .unwrap_log("Failed to load key bindings");
 
+    // amethyst's bundles add their systems to the dispatcher directly, so the systems
+    // of the transform, input, hot reload, UI and render bundles aren't timed
     let game_data = Ok(GameDataBuilder::new())
         .and_then(|data| data.with_bundle(TransformBundle::new()))
         .and_then(|data| data.with_bundle(input_bundle))
-        .map(|data| data.with(Processor::<Source>::new(), "source_processor", &[]))
-        .map(|data| data.with(MouseFocusUpdateSystem::new(), "focus", &[]))
-        .map(|data| data.with(CursorHideSystem::new(), "cursor_hide", &["focus"]))
-        .map(|data| data.with(ecs::mainmenu::MainMenuRotation::new(0.7), "rotates", &[]))
-        .map(|data| data.with(ecs::camera::CameraAspect::new(), "camera_aspect", &[]))
-        .map(|data| {
-            data.with(
-                ecs::gameplay::CameraMovement::new(3.0),
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                Processor::<Source>::new(),
+                "source_processor",
+                &[],
+            )
+        })
+        .map(|data| data.with_timed(&profiler, Processor::<Tips>::new(), "tips_processor", &[]))
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                Processor::<StringTable>::new(),
+                "strings_processor",
+                &[],
+            )
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                Processor::<MeshGeometry>::new(),
+                "geometry_processor",
+                &[],
+            )
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                PrefabLoaderSystem::<scene::ScenePrefabData>::default(),
+                "scene_loader",
+                &[],
+            )
+        })
+        .map(|data| data.with_timed(&profiler, MouseFocusUpdateSystem::new(), "focus", &[]))
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                CursorHideSystem::new(),
+                "cursor_hide",
+                &["focus"],
+            )
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                ecs::mainmenu::MainMenuRotation::new(0.7),
+                "rotates",
+                &[],
+            )
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                ecs::camera::CameraProjection::default(),
+                "camera_projection",
+                &[],
+            )
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                ecs::lighting::AmbientLightSystem::default(),
+                "ambient_light",
+                &[],
+            )
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                ecs::daynight::DayNightCycle::new(day_night_config),
+                "day_night",
+                &["ambient_light"],
+            )
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                ecs::gameplay::CameraMovement::new(3.0, 2.0),
                 "camera_movement",
                 &["focus"],
             )
         })
         .map(|data| {
-            data.with(
+            data.with_timed(
+                &profiler,
                 ecs::gameplay::CameraRotation::new(0.1, 0.1),
                 "camera_rotation",
                 &["focus"],
             )
         })
+        .and_then(|data| {
+            if dev {
+                info!("Development mode, watching assets and config for changes");
+                // the pack doesn't change while running, so its assets can't be reloaded
+                let watched_assets = if pack.is_some() {
+                    warn!("Hot reload of assets is disabled, using {}", PACK_FILE);
+                    None
+                } else {
+                    Some(assets_path.clone())
+                };
+                data.with_bundle(HotReloadBundle::default()).map(|data| {
+                    data.with_timed(
+                        &profiler,
+                        HotReload::new(watched_assets, config_path.clone(), 1.0),
+                        "hot_reload",
+                        &[],
+                    )
+                })
+            } else {
+                Ok(data)
+            }
+        })
         .and_then(|data| data.with_bundle(UiBundle::<String, String>::new()))
-        .and_then(|data| graphics::add_renderer(data, &display_path))
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                localization::LocalizationSystem::default(),
+                "localization",
+                &["strings_processor"],
+            )
+        })
+        .and_then(|data| data.with_bundle(ui::WidgetBundle::new(&profiler)))
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                ecs::debug::DebugStatsSystem::new(log_stats),
+                "debug_stats",
+                &[],
+            )
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                ecs::debug::DebugOverlay::default(),
+                "debug_overlay",
+                &["debug_stats"],
+            )
+        })
+        .map(|data| match &profile_path {
+            Some(path) => data.with(ProfileExport::new(path.clone()), "profile_export", &[]),
+            None => data,
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                screenshot::ScreenshotSystem::default(),
+                "screenshot",
+                &[],
+            )
+        })
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                ecs::debug_draw::DebugDraw::default(),
+                "debug_draw",
+                &[],
+            )
+        })
+        .map(|data| data.with_timed(&profiler, ecs::culling::LodSystem::default(), "lod", &[]))
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                ecs::culling::CullingSystem::default(),
+                "culling",
+                &["lod", "debug_stats"],
+            )
+        })
+        .map(|data| data.with_timed(&profiler, display::DisplaySystem::default(), "display", &[]))
+        .map(|data| {
+            data.with_timed(
+                &profiler,
+                frame_rate::FrameRateSystem::default(),
+                "frame_rate",
+                &["focus"],
+            )
+        })
+        .and_then(|data| graphics::add_renderer(data, &display_path, &settings, &overrides))
         .unwrap_log("Failed to create Game Data");
 
-    let application = Application::build(assets_path, LoadingState::new())
+    let application = Application::build(&assets_path, LoadingState::new())
+        .map(|mut app| {
+            if let Some(pack) = pack.clone() {
+                app.world.write_resource::<Loader>().set_default_source(pack);
+            }
+            app
+        })
+        .map(|app| app.with_resource(AssetCache::new(assets_path.clone(), pack)))
+        .map(|app| app.with_resource(ChangedAssets::default()))
         .map(|app| app.with_resource(HideCursor { hide: false }))
         .map(|app| app.with_resource(CurrentState::Loading))
+        .map(|app| app.with_resource(WorldTime::new(start_hour)))
+        .map(|app| app.with_resource(loading_config))
+        .map(|app| app.with_resource(settings))
+        .map(|app| app.with_resource(overrides))
+        .map(|app| app.with_resource(DebugLines::new()))
+        .map(|app| app.with_resource(localization::Localization::default()))
+        .map(|app| app.with_resource(profiler.clone()))
+        .map(|app| match benchmark {
+            Some(benchmark) => app.with_resource(benchmark),
+            None => app,
+        })
         .and_then(|app| app.build(game_data));
 
     match application {
         Ok(mut game) => {
             info!("Starting {} [{}]...", NAME, VERSION);
             game.run();
+
+            if let Some(path) = profile_path {
+                profiler.log_summary();
+                match profiler.export(&path) {
+                    Ok(()) => info!("Wrote profile to {}", path.display()),
+                    Err(err) => error!("Failed to write profile: {}", err),
+                }
+            }
         }
         Err(err) => {
             error!("Failed to initialize: {}", err);
         }
     }
 }
+
+fn parse_resolution(resolution: &str) -> Option<(u32, u32)> {
+    let mut parts = resolution.split('x');
+    let width = parts.next()?.trim().parse().ok()?;
+    let height = parts.next()?.trim().parse().ok()?;
+    if parts.next().is_some() || width == 0 || height == 0 {
+        return None;
+    }
+    Some((width, height))
+}
@@ -0,0 +1,358 @@
+use crate::logger::prelude::*;
+use amethyst::{
+    core::{shrev::EventChannel, specs::prelude::*, timing::Time},
+    input::InputEvent,
+    GameDataBuilder,
+};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fs,
+    io::{self, Write as IoWrite},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
+};
+
+// upper bounds of the histogram buckets in milliseconds, the last bucket is open
+const BUCKETS: &[f32] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 33.0];
+// older spans are dropped from the trace, so long sessions don't grow without bounds
+const MAX_SPANS: usize = 200_000;
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000 + u64::from(duration.subsec_micros())
+}
+
+struct Span {
+    name: &'static str,
+    thread: usize,
+    start: Duration,
+    duration: Duration,
+}
+
+#[derive(Default)]
+struct Histogram {
+    count: u64,
+    total: f32,
+    max: f32,
+    buckets: [u64; 11],
+}
+
+impl Histogram {
+    fn add(&mut self, millis: f32) {
+        let bucket = BUCKETS
+            .iter()
+            .position(|&bound| millis <= bound)
+            .unwrap_or(BUCKETS.len());
+
+        self.count += 1;
+        self.total += millis;
+        self.max = self.max.max(millis);
+        self.buckets[bucket] += 1;
+    }
+}
+
+struct ProfileData {
+    epoch: Instant,
+    threads: HashMap<ThreadId, usize>,
+    spans: VecDeque<Span>,
+    histograms: BTreeMap<&'static str, Histogram>,
+    frame: u64,
+    frame_start: Instant,
+}
+
+impl ProfileData {
+    fn record(&mut self, name: &'static str, start: Instant, duration: Duration) {
+        let thread_count = self.threads.len();
+        let thread = *self
+            .threads
+            .entry(thread::current().id())
+            .or_insert(thread_count);
+
+        if self.spans.len() >= MAX_SPANS {
+            self.spans.pop_front();
+        }
+        self.spans.push_back(Span {
+            name,
+            thread,
+            start: start - self.epoch,
+            duration,
+        });
+
+        self.histograms
+            .entry(name)
+            .or_default()
+            .add(micros(duration) as f32 / 1000.0);
+    }
+}
+
+// Collects how long the systems and state updates take, shared between the wrapped
+// systems and the world. Does nothing unless enabled
+#[derive(Clone)]
+pub struct Profiler {
+    enabled: bool,
+    data: Arc<Mutex<ProfileData>>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler::new(false)
+    }
+}
+
+impl Profiler {
+    pub fn new(enabled: bool) -> Profiler {
+        let now = Instant::now();
+
+        Profiler {
+            enabled,
+            data: Arc::new(Mutex::new(ProfileData {
+                epoch: now,
+                threads: HashMap::new(),
+                spans: VecDeque::new(),
+                histograms: BTreeMap::new(),
+                frame: 0,
+                frame_start: now,
+            })),
+        }
+    }
+
+    // measures until the returned guard is dropped
+    pub fn scope(&self, name: &'static str) -> Scope<'_> {
+        Scope {
+            profiler: self,
+            name,
+            start: if self.enabled {
+                Some(Instant::now())
+            } else {
+                None
+            },
+        }
+    }
+
+    // the first span of a new frame closes the previous frame
+    fn frame(&self, frame: u64) {
+        let mut data = self.data.lock().unwrap();
+        if data.frame == frame {
+            return;
+        }
+
+        let start = data.frame_start;
+        let now = Instant::now();
+        if data.frame != 0 {
+            data.record("frame", start, now - start);
+        }
+        data.frame = frame;
+        data.frame_start = now;
+    }
+
+    // wraps the system, so it is timed under the name
+    pub fn timed<S>(&self, system: S, name: &'static str) -> Timed<S> {
+        Timed {
+            system,
+            name,
+            profiler: self.clone(),
+        }
+    }
+
+    fn record(&self, name: &'static str, start: Instant) {
+        let duration = start.elapsed();
+        self.data.lock().unwrap().record(name, start, duration);
+    }
+
+    // a .csv file gets the histograms, anything else a Chrome trace (chrome://tracing)
+    pub fn export(&self, path: &Path) -> io::Result<()> {
+        let data = self.data.lock().unwrap();
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+
+        if path
+            .extension()
+            .map_or(false, |extension| extension == "csv")
+        {
+            write_csv(&data, &mut file)?;
+        } else {
+            write_trace(&data, &mut file)?;
+        }
+
+        file.flush()
+    }
+
+    pub fn log_summary(&self) {
+        let data = self.data.lock().unwrap();
+
+        for (name, histogram) in &data.histograms {
+            info!(
+                "{}: {} runs, avg {:.3} ms, max {:.3} ms",
+                name,
+                histogram.count,
+                histogram.total / histogram.count.max(1) as f32,
+                histogram.max
+            );
+        }
+    }
+}
+
+fn write_csv(data: &ProfileData, out: &mut impl IoWrite) -> io::Result<()> {
+    write!(out, "name,count,total_ms,avg_ms,max_ms")?;
+    for bound in BUCKETS {
+        write!(out, ",le_{}ms", bound)?;
+    }
+    writeln!(out, ",gt_{}ms", BUCKETS[BUCKETS.len() - 1])?;
+
+    for (name, histogram) in &data.histograms {
+        write!(
+            out,
+            "{},{},{:.3},{:.4},{:.4}",
+            name,
+            histogram.count,
+            histogram.total,
+            histogram.total / histogram.count.max(1) as f32,
+            histogram.max
+        )?;
+        for count in histogram.buckets.iter() {
+            write!(out, ",{}", count)?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+// complete events of the trace event format, times are in microseconds
+fn write_trace(data: &ProfileData, out: &mut impl IoWrite) -> io::Result<()> {
+    writeln!(out, "{{\"traceEvents\":[")?;
+
+    for (index, span) in data.spans.iter().enumerate() {
+        writeln!(
+            out,
+            "{{\"name\":\"{}\",\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{},\"dur\":{}}}{}",
+            span.name,
+            span.thread,
+            micros(span.start),
+            micros(span.duration),
+            if index + 1 < data.spans.len() {
+                ","
+            } else {
+                ""
+            }
+        )?;
+    }
+
+    writeln!(out, "],\"displayTimeUnit\":\"ms\"}}")
+}
+
+pub struct Scope<'a> {
+    profiler: &'a Profiler,
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl<'a> Drop for Scope<'a> {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            self.profiler.record(self.name, start);
+        }
+    }
+}
+
+// Runs a system and records how long it took
+pub struct Timed<S> {
+    system: S,
+    name: &'static str,
+    profiler: Profiler,
+}
+
+impl<'a, S> System<'a> for Timed<S>
+where
+    S: System<'a>,
+{
+    type SystemData = (Read<'a, Time>, S::SystemData);
+
+    fn setup(&mut self, res: &mut Resources) {
+        <Read<'a, Time>>::setup(res);
+        self.system.setup(res);
+    }
+
+    fn run(&mut self, (time, data): Self::SystemData) {
+        if !self.profiler.enabled {
+            self.system.run(data);
+            return;
+        }
+
+        self.profiler.frame(time.frame_number());
+        let start = Instant::now();
+        self.system.run(data);
+        self.profiler.record(self.name, start);
+    }
+}
+
+pub trait WithTimed {
+    // like with, but the system is timed by the profiler
+    fn with_timed<S>(
+        self,
+        profiler: &Profiler,
+        system: S,
+        name: &'static str,
+        dependencies: &[&str],
+    ) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'static;
+}
+
+impl<'a, 'b> WithTimed for GameDataBuilder<'a, 'b> {
+    fn with_timed<S>(
+        self,
+        profiler: &Profiler,
+        system: S,
+        name: &'static str,
+        dependencies: &[&str],
+    ) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'static,
+    {
+        self.with(profiler.timed(system, name), name, dependencies)
+    }
+}
+
+// Writes the profile when the profile_export action is pressed
+pub struct ProfileExport {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+    path: PathBuf,
+}
+
+impl ProfileExport {
+    pub fn new(path: PathBuf) -> Self {
+        ProfileExport {
+            event_reader: None,
+            path,
+        }
+    }
+}
+
+impl<'a> System<'a> for ProfileExport {
+    type SystemData = (
+        Read<'a, EventChannel<InputEvent<String>>>,
+        ReadExpect<'a, Profiler>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(&mut self, (events, profiler): Self::SystemData) {
+        for event in events.read(self.event_reader.as_mut().unwrap()) {
+            if let InputEvent::ActionPressed(action) = event {
+                if action == "profile_export" {
+                    match profiler.export(&self.path) {
+                        Ok(()) => info!("Wrote profile to {}", self.path.display()),
+                        Err(err) => error!("Failed to write profile: {}", err),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,454 @@
+use crate::{
+    assets::{
+        AssetCache, AssetProgress, GeometryFormat, MeshGeometry, PendingAssets, TangentObjFormat,
+    },
+    ecs::{
+        culling::{Lod, LodLevel},
+        daynight::Sun,
+        lighting::Ambient,
+        mainmenu::Rotates,
+    },
+    graphics,
+    settings::{Settings, SettingsOverrides, Shading},
+};
+use amethyst::{
+    assets::{
+        AssetStorage, Handle, Loader, Prefab, PrefabData, PrefabError, ProgressCounter,
+        Result as AssetResult, RonFormat, SimpleFormat,
+    },
+    core::{
+        nalgebra::{Translation3, UnitQuaternion, Vector3},
+        specs::prelude::*,
+        Transform,
+    },
+    derive::PrefabData,
+    prelude::*,
+    renderer::{
+        Camera, DirectionalLight, Light, Material, MaterialDefaults, Mesh, ObjFormat, PointLight,
+        Rgba, ScreenDimensions, SpotLight, Texture,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+pub type SceneHandle = Handle<Prefab<ScenePrefabData>>;
+
+// loads "scene/<name>.ron", owned by the given state
+pub fn load(
+    world: &mut World,
+    owner: &str,
+    name: &str,
+    progress: &mut AssetProgress,
+) -> SceneHandle {
+    let path = format!("scene/{}.ron", name);
+    let pending = progress.pending();
+
+    world.exec(
+        |(mut cache, loader, storage): (
+            WriteExpect<'_, AssetCache>,
+            ReadExpect<'_, Loader>,
+            Read<'_, AssetStorage<Prefab<ScenePrefabData>>>,
+        )| {
+            cache.load(
+                owner,
+                &path,
+                SceneFormat,
+                (path.clone(), pending),
+                progress.named(path.as_str()),
+                &loader,
+                &storage,
+            )
+        },
+    )
+}
+
+// Reads a scene from RON. The assets of its models are cached with the scene's path as
+// their owner, so they are released together with the scene, and shown by name while
+// they are loading
+#[derive(Clone)]
+pub struct SceneFormat;
+
+impl SimpleFormat<Prefab<ScenePrefabData>> for SceneFormat {
+    const NAME: &'static str = "SCENE_RON";
+    // the path of the scene and where its models' assets are named
+    type Options = (String, PendingAssets);
+
+    fn import(
+        &self,
+        bytes: Vec<u8>,
+        (path, pending): (String, PendingAssets),
+    ) -> AssetResult<Prefab<ScenePrefabData>> {
+        let mut prefab: Prefab<ScenePrefabData> = RonFormat.import(bytes, ())?;
+        for index in 0..prefab.len() {
+            let model = prefab
+                .entity(index)
+                .and_then(|entity| entity.data_mut())
+                .and_then(|data| data.model.as_mut());
+            if let Some(model) = model {
+                model.scene = path.clone();
+                model.pending = pending.clone();
+            }
+        }
+
+        Ok(prefab)
+    }
+}
+
+// the returned root entity owns every entity of the scene
+pub fn spawn(world: &mut World, scene: &SceneHandle) -> Entity {
+    world.create_entity().with(scene.clone()).build()
+}
+
+#[derive(Default, Deserialize, Serialize, PrefabData)]
+#[serde(default)]
+pub struct ScenePrefabData {
+    transform: Option<SceneTransform>,
+    model: Option<SceneModel>,
+    camera: Option<SceneCamera>,
+    light: Option<SceneLight>,
+    #[prefab(Component)]
+    rotates: Option<Rotates>,
+}
+
+// rotation is given in degrees around the x, y and z axis
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SceneTransform {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+impl Default for SceneTransform {
+    fn default() -> Self {
+        SceneTransform {
+            translation: [0.0; 3],
+            rotation: [0.0; 3],
+            scale: [1.0; 3],
+        }
+    }
+}
+
+impl<'a> PrefabData<'a> for SceneTransform {
+    type SystemData = WriteStorage<'a, Transform>;
+    type Result = ();
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        transforms: &mut Self::SystemData,
+        _: &[Entity],
+    ) -> Result<(), PrefabError> {
+        let [x, y, z] = self.translation;
+        let [roll, pitch, yaw] = self.rotation;
+        let [scale_x, scale_y, scale_z] = self.scale;
+
+        let transform = Transform::new(
+            Translation3::new(x, y, z),
+            UnitQuaternion::from_euler_angles(
+                roll.to_radians(),
+                pitch.to_radians(),
+                yaw.to_radians(),
+            ),
+            Vector3::new(scale_x, scale_y, scale_z),
+        );
+        transforms.insert(entity, transform)?;
+
+        Ok(())
+    }
+}
+
+// an obj mesh with a solid color albedo
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SceneModel {
+    pub mesh: String,
+    pub albedo: [f32; 4],
+    // lower detail meshes, used from their distance to the camera on
+    #[serde(default)]
+    pub lods: Vec<SceneLod>,
+    // the path of the scene the model is in, owns the model's cached assets
+    #[serde(skip)]
+    scene: String,
+    #[serde(skip)]
+    pending: PendingAssets,
+    #[serde(skip)]
+    handles: Option<(Handle<Mesh>, Handle<MeshGeometry>, Handle<Texture>)>,
+    #[serde(skip)]
+    lod_handles: Vec<LodLevel>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SceneLod {
+    pub distance: f32,
+    pub mesh: String,
+}
+
+impl<'a> PrefabData<'a> for SceneModel {
+    type SystemData = (
+        WriteExpect<'a, AssetCache>,
+        ReadExpect<'a, Loader>,
+        Read<'a, Settings>,
+        Read<'a, SettingsOverrides>,
+        Read<'a, AssetStorage<Mesh>>,
+        Read<'a, AssetStorage<MeshGeometry>>,
+        Read<'a, AssetStorage<Texture>>,
+        ReadExpect<'a, MaterialDefaults>,
+        WriteStorage<'a, Handle<Mesh>>,
+        WriteStorage<'a, Handle<MeshGeometry>>,
+        WriteStorage<'a, Material>,
+        WriteStorage<'a, Lod>,
+    );
+    type Result = ();
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        (.., material_defaults, meshes, geometries, materials, lods): &mut Self::SystemData,
+        _: &[Entity],
+    ) -> Result<(), PrefabError> {
+        let (mesh, geometry, albedo) = self
+            .handles
+            .clone()
+            .ok_or_else(|| PrefabError::Custom("Model assets were not loaded".into()))?;
+
+        if !self.lod_handles.is_empty() {
+            let mut levels = vec![LodLevel {
+                distance: 0.0,
+                mesh: mesh.clone(),
+                geometry: geometry.clone(),
+            }];
+            levels.extend(self.lod_handles.iter().cloned());
+            lods.insert(entity, Lod::new(levels))?;
+        }
+        meshes.insert(entity, mesh)?;
+        geometries.insert(entity, geometry)?;
+        materials.insert(
+            entity,
+            Material {
+                albedo,
+                ..material_defaults.0.clone()
+            },
+        )?;
+
+        Ok(())
+    }
+
+    fn load_sub_assets(
+        &mut self,
+        progress: &mut ProgressCounter,
+        (
+            cache,
+            loader,
+            settings,
+            overrides,
+            mesh_storage,
+            geometry_storage,
+            texture_storage,
+            ..
+        ): &mut Self::SystemData,
+    ) -> Result<bool, PrefabError> {
+        if let Some(lod) = self.lods.iter().find(|lod| lod.distance.is_nan()) {
+            return Err(PrefabError::Custom(
+                format!("Level of detail {} has a NaN distance", lod.mesh).into(),
+            ));
+        }
+
+        // the shading passes need different vertex formats
+        let pbr = overrides.shading(&settings.graphics) == Shading::Pbr;
+        let owner = &self.scene;
+        let pending = &self.pending;
+        // every level of detail has its own bounds
+        let mut load_model = |path: &str| {
+            let mesh = if pbr {
+                cache.load(
+                    owner,
+                    path,
+                    TangentObjFormat,
+                    (),
+                    pending.named(&mut *progress, path),
+                    loader,
+                    mesh_storage,
+                )
+            } else {
+                cache.load(
+                    owner,
+                    path,
+                    ObjFormat,
+                    (),
+                    pending.named(&mut *progress, path),
+                    loader,
+                    mesh_storage,
+                )
+            };
+            let geometry = cache.load(
+                owner,
+                path,
+                GeometryFormat,
+                (),
+                pending.named(&mut *progress, format!("{} (geometry)", path)),
+                loader,
+                geometry_storage,
+            );
+            (mesh, geometry)
+        };
+        let (mesh, geometry) = load_model(&self.mesh);
+        self.lod_handles = self
+            .lods
+            .iter()
+            .map(|lod| {
+                let (mesh, geometry) = load_model(&lod.mesh);
+                LodLevel {
+                    distance: lod.distance,
+                    mesh,
+                    geometry,
+                }
+            })
+            .collect();
+
+        let albedo = loader.load_from_data(self.albedo.into(), &mut *progress, &*texture_storage);
+        self.handles = Some((mesh, geometry, albedo));
+
+        Ok(true)
+    }
+}
+
+// a camera matching the aspect ratio of the window and the camera settings
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct SceneCamera;
+
+impl<'a> PrefabData<'a> for SceneCamera {
+    type SystemData = (
+        ReadExpect<'a, ScreenDimensions>,
+        Read<'a, Settings>,
+        WriteStorage<'a, Camera>,
+    );
+    type Result = ();
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        (dimensions, settings, cameras): &mut Self::SystemData,
+        _: &[Entity],
+    ) -> Result<(), PrefabError> {
+        cameras.insert(
+            entity,
+            graphics::camera(dimensions.aspect_ratio(), &settings.camera),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub enum SceneLight {
+    // a directional light driven by the day/night cycle
+    Sun,
+    // lights the whole scene from one direction
+    Directional(LightSettings),
+    // shines in all directions from the entity's position
+    Point(LightSettings),
+    // a cone from the entity's position along the direction
+    Spot(LightSettings),
+    // added to the ambient light while the scene is loaded
+    Ambient(LightSettings),
+}
+
+// not every kind of light uses every field
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LightSettings {
+    pub color: [f32; 4],
+    pub intensity: f32,
+    // how far point and spot lights reach
+    pub radius: f32,
+    // opening angle of spot lights in degrees
+    pub angle: f32,
+    // of directional and spot lights
+    pub direction: [f32; 3],
+}
+
+impl Default for LightSettings {
+    fn default() -> Self {
+        LightSettings {
+            color: [1.0, 1.0, 1.0, 1.0],
+            intensity: 1.0,
+            radius: 10.0,
+            angle: 45.0,
+            direction: [0.0, -1.0, 0.0],
+        }
+    }
+}
+
+impl LightSettings {
+    fn color(&self) -> Rgba {
+        let [red, green, blue, alpha] = self.color;
+        Rgba(red, green, blue, alpha)
+    }
+
+    // for the lights without an intensity of their own
+    fn scaled_color(&self) -> Rgba {
+        let [red, green, blue, alpha] = self.color;
+        let intensity = self.intensity;
+        Rgba(red * intensity, green * intensity, blue * intensity, alpha)
+    }
+}
+
+impl<'a> PrefabData<'a> for SceneLight {
+    type SystemData = (
+        WriteStorage<'a, Light>,
+        WriteStorage<'a, Sun>,
+        WriteStorage<'a, Ambient>,
+    );
+    type Result = ();
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        (lights, suns, ambients): &mut Self::SystemData,
+        _: &[Entity],
+    ) -> Result<(), PrefabError> {
+        match self {
+            SceneLight::Sun => {
+                lights.insert(entity, DirectionalLight::default().into())?;
+                suns.insert(entity, Sun)?;
+            }
+            SceneLight::Ambient(settings) => {
+                ambients.insert(
+                    entity,
+                    Ambient {
+                        color: settings.color,
+                        intensity: settings.intensity,
+                    },
+                )?;
+            }
+            SceneLight::Directional(settings) => {
+                let light = DirectionalLight {
+                    color: settings.scaled_color(),
+                    direction: settings.direction,
+                };
+                lights.insert(entity, light.into())?;
+            }
+            SceneLight::Point(settings) => {
+                let light = PointLight {
+                    color: settings.color(),
+                    intensity: settings.intensity,
+                    radius: settings.radius,
+                    ..PointLight::default()
+                };
+                lights.insert(entity, light.into())?;
+            }
+            SceneLight::Spot(settings) => {
+                let light = SpotLight {
+                    angle: settings.angle.to_radians(),
+                    color: settings.color(),
+                    direction: settings.direction,
+                    intensity: settings.intensity,
+                    range: settings.radius,
+                    ..SpotLight::default()
+                };
+                lights.insert(entity, light.into())?;
+            }
+        }
+
+        Ok(())
+    }
+}
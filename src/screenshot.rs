@@ -0,0 +1,227 @@
+use crate::{
+    graphics,
+    logger::{self, prelude::*},
+    settings::Settings,
+};
+use amethyst::{
+    core::{shrev::EventChannel, specs::prelude::*},
+    input::InputEvent,
+    renderer::{
+        pipe::{
+            pass::{Pass, PassData},
+            Effect, NewEffect,
+        },
+        Encoder, Factory, Hidden,
+    },
+    ui::UiTransform,
+};
+use gfx::{
+    format::{ChannelType, Format, SurfaceType},
+    handle::Buffer,
+    texture::ImageInfoCommon,
+    Factory as GfxFactory,
+};
+use gfx_device_gl::Resources as GlResources;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+    thread,
+};
+
+const SCREENSHOT_DIR: &str = "screenshots";
+
+// used as a resource, shared between the ScreenshotSystem and the DrawScreenshot pass
+#[derive(Default)]
+pub struct Screenshot {
+    // the next rendered frame is captured
+    requested: bool,
+    // UI elements hidden for the capture, shown again afterwards
+    hidden: Vec<Entity>,
+}
+
+// Requests a screenshot when the screenshot action is pressed, hiding the UI first
+// if the settings say so
+#[derive(Default)]
+pub struct ScreenshotSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+impl<'a> System<'a> for ScreenshotSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EventChannel<InputEvent<String>>>,
+        Read<'a, Settings>,
+        Write<'a, Screenshot>,
+        ReadStorage<'a, UiTransform>,
+        WriteStorage<'a, Hidden>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(
+        &mut self,
+        (entities, events, settings, mut screenshot, transforms, mut hidden): Self::SystemData,
+    ) {
+        // the capture happened last frame
+        if !screenshot.requested {
+            for entity in screenshot.hidden.drain(..) {
+                hidden.remove(entity);
+            }
+        }
+
+        let pressed = events
+            .read(self.event_reader.as_mut().unwrap())
+            .any(|event| match event {
+                InputEvent::ActionPressed(action) => action == "screenshot",
+                _ => false,
+            });
+        if !pressed || screenshot.requested {
+            return;
+        }
+
+        // the pass runs after this system, so hiding takes effect in the captured frame
+        if settings.screenshot_hide_ui {
+            let visible: Vec<_> = (&*entities, &transforms, !&hidden)
+                .join()
+                .map(|(entity, _, _)| entity)
+                .collect();
+            for &entity in &visible {
+                hidden.insert(entity, Hidden).unwrap();
+            }
+            screenshot.hidden = visible;
+        }
+        screenshot.requested = true;
+    }
+}
+
+// Copies the backbuffer into a download buffer after everything else was drawn,
+// the pixels are read back a frame later once the GPU is done with them
+#[derive(Default)]
+pub struct DrawScreenshot {
+    pending: Option<(Buffer<GlResources, [u8; 4]>, u32, u32)>,
+}
+
+impl DrawScreenshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a> PassData<'a> for DrawScreenshot {
+    type Data = Write<'a, Screenshot>;
+}
+
+impl Pass for DrawScreenshot {
+    // the pass only needs an effect to get at the color target
+    fn compile(&mut self, effect: NewEffect) -> amethyst::renderer::error::Result<Effect> {
+        graphics::compile_placeholder(effect)
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        mut factory: Factory,
+        mut screenshot: Write<'a, Screenshot>,
+    ) {
+        if let Some((buffer, width, height)) = self.pending.take() {
+            match factory.read_mapping(&buffer) {
+                Ok(pixels) => {
+                    let pixels: Vec<[u8; 4]> = pixels.to_vec();
+                    thread::spawn(move || save(pixels, width, height));
+                }
+                Err(err) => error!("Failed to read back screenshot: {:?}", err),
+            }
+        }
+
+        if !screenshot.requested {
+            return;
+        }
+        screenshot.requested = false;
+
+        let texture = match effect.data.out_colors.first() {
+            Some(target) => target.get_texture().clone(),
+            None => return,
+        };
+        let (width, height, _, _) = texture.get_info().kind.get_dimensions();
+        let (width, height) = (u32::from(width), u32::from(height));
+
+        let buffer = match factory.create_download_buffer::<[u8; 4]>((width * height) as usize) {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                error!("Failed to create screenshot buffer: {:?}", err);
+                return;
+            }
+        };
+        let info = ImageInfoCommon {
+            xoffset: 0,
+            yoffset: 0,
+            zoffset: 0,
+            width: width as u16,
+            height: height as u16,
+            depth: 0,
+            format: Format(SurfaceType::R8_G8_B8_A8, ChannelType::Srgb),
+            mipmap: 0,
+        };
+
+        match encoder.copy_texture_to_buffer_raw(&texture, None, info, buffer.raw(), 0) {
+            Ok(()) => self.pending = Some((buffer, width, height)),
+            Err(err) => error!("Failed to capture screenshot: {:?}", err),
+        }
+    }
+}
+
+// writes the pixels, which are stored bottom row first
+fn save(pixels: Vec<[u8; 4]>, width: u32, height: u32) {
+    let path = match screenshot_path() {
+        Ok(path) => path,
+        Err(err) => {
+            error!("Failed to create {} folder: {}", SCREENSHOT_DIR, err);
+            return;
+        }
+    };
+
+    let data: Vec<u8> = pixels
+        .chunks(width as usize)
+        .rev()
+        .flat_map(|row| row.iter().flat_map(|pixel| pixel.iter().cloned()))
+        .collect();
+
+    match write_png(&path, &data, width, height) {
+        Ok(()) => info!("Saved screenshot to {}", path.display()),
+        Err(err) => error!("Failed to save screenshot: {}", err),
+    }
+}
+
+// screenshots taken in the same second get a number appended
+fn screenshot_path() -> io::Result<PathBuf> {
+    fs::create_dir_all(SCREENSHOT_DIR)?;
+
+    let time = logger::format_yyyymmdd_hhmmss();
+    let mut path = Path::new(SCREENSHOT_DIR).join(format!("{}.png", time));
+    let mut number = 1;
+    while path.exists() {
+        number += 1;
+        path = Path::new(SCREENSHOT_DIR).join(format!("{}-{}.png", time, number));
+    }
+
+    Ok(path)
+}
+
+fn write_png(path: &Path, data: &[u8], width: u32, height: u32) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set(png::ColorType::RGBA).set(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(data))
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+}
@@ -0,0 +1,270 @@
+use crate::logger::prelude::*;
+use amethyst::config::Config;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// the player's settings, stored in config/settings.ron
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    // multiplies the size of the menus, on top of fitting them to the window
+    pub ui_scale: f32,
+    // selects the string tables in assets/text
+    pub language: String,
+    // screenshots are taken without the menus and the debug overlay
+    pub screenshot_hide_ui: bool,
+    pub camera: CameraSettings,
+    pub graphics: GraphicsSettings,
+    pub display: DisplaySettings,
+    pub frame_rate: FrameRateSettings,
+    // where the settings are saved to
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            ui_scale: 1.0,
+            language: "en".into(),
+            screenshot_hide_ui: false,
+            camera: CameraSettings::default(),
+            graphics: GraphicsSettings::default(),
+            display: DisplaySettings::default(),
+            frame_rate: FrameRateSettings::default(),
+            path: PathBuf::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum Projection {
+    Perspective,
+    // without perspective distortion, for debugging
+    Orthographic,
+}
+
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct CameraSettings {
+    pub projection: Projection,
+    // vertical field of view in degrees
+    pub fov: f32,
+    pub near: f32,
+    pub far: f32,
+    // half the height of the view in world units, for the orthographic projection
+    pub ortho_size: f32,
+    // added to the field of view while sprinting
+    pub sprint_fov_kick: f32,
+    // the field of view while zooming
+    pub zoom_fov: f32,
+    // how fast the field of view follows sprinting and zooming, higher is faster
+    pub fov_smoothing: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            projection: Projection::Perspective,
+            fov: 45.0,
+            near: 0.1,
+            far: 2000.0,
+            ortho_size: 5.0,
+            sprint_fov_kick: 10.0,
+            zoom_fov: 20.0,
+            fov_smoothing: 10.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum Shading {
+    // unlit, only the albedo
+    Flat,
+    Shaded,
+    // physically based, needs meshes with tangents
+    Pbr,
+}
+
+impl Shading {
+    pub const ALL: &'static [Shading] = &[Shading::Flat, Shading::Shaded, Shading::Pbr];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shading::Flat => "flat",
+            Shading::Shaded => "shaded",
+            Shading::Pbr => "pbr",
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    // the pipeline is built once, so these apply on the next start
+    pub clear_color: [f32; 4],
+    pub shading: Shading,
+    // the day/night cycle colors the sky through the skybox. Without it the background
+    // stays the clear color, as the clear color can't change after the pipeline is built
+    pub skybox: bool,
+    // debug lines drawn over the meshes, these apply right away
+    pub wireframe: bool,
+    pub normals: bool,
+    // length of the drawn normals in world units
+    pub normal_length: f32,
+    // meshes outside of the view or the draw distance aren't drawn
+    pub culling: bool,
+    pub draw_distance: f32,
+    // multiplies the distances the levels of detail switch at
+    pub lod_bias: f32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        GraphicsSettings {
+            clear_color: [0.3, 0.3, 0.3, 1.0],
+            shading: Shading::Shaded,
+            skybox: true,
+            wireframe: false,
+            normals: false,
+            normal_length: 0.1,
+            culling: true,
+            draw_distance: 500.0,
+            lod_bias: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum DisplayMode {
+    Windowed,
+    // a window without decorations covering the monitor
+    Borderless,
+    // the window covers the monitor at the desktop resolution, winit can't switch video
+    // modes for an exclusive fullscreen
+    #[serde(alias = "Fullscreen")]
+    DesktopFullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: &'static [DisplayMode] = &[
+        DisplayMode::Windowed,
+        DisplayMode::Borderless,
+        DisplayMode::DesktopFullscreen,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "windowed",
+            DisplayMode::Borderless => "borderless",
+            DisplayMode::DesktopFullscreen => "desktop_fullscreen",
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub mode: DisplayMode,
+    // size of the window in windowed mode, display.ron decides if unset.
+    // Updated when the window is resized
+    pub resolution: Option<(u32, u32)>,
+    // index into the available monitors, the primary monitor if out of range
+    pub monitor: usize,
+    // where the window was last moved to, in windowed mode
+    pub position: Option<(i32, i32)>,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        DisplaySettings {
+            mode: DisplayMode::Windowed,
+            resolution: None,
+            monitor: 0,
+            position: None,
+        }
+    }
+}
+
+// how the frame limiter waits for the next frame
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum LimitStrategy {
+    // busy waits, the most precise
+    Yield,
+    // gives the time to other programs, but may oversleep
+    Sleep,
+    // sleeps and yields for the last milliseconds
+    SleepAndYield,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FrameRateSettings {
+    // frames per second, unlimited if unset
+    pub max_fps: Option<u32>,
+    pub strategy: LimitStrategy,
+    // used instead while the window isn't focused, to save power
+    pub unfocused_fps: Option<u32>,
+    // needs a restart, the swap interval is set when the window is created and
+    // glutin can't change it later
+    pub vsync: bool,
+}
+
+impl Default for FrameRateSettings {
+    fn default() -> Self {
+        FrameRateSettings {
+            max_fps: None,
+            strategy: LimitStrategy::SleepAndYield,
+            unfocused_fps: Some(15),
+            vsync: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn open(path: impl AsRef<Path>) -> Settings {
+        Settings {
+            path: path.as_ref().to_path_buf(),
+            ..Settings::load(path)
+        }
+    }
+
+    pub fn save(&self) {
+        match self.write(&self.path) {
+            Ok(()) => info!("Saved settings to {}", self.path.display()),
+            Err(err) => error!("Failed to save settings: {}", err),
+        }
+    }
+}
+
+// used as a resource, settings given on the command line. They are used instead of the
+// player's settings but never saved. The display overrides are dropped once the player
+// changes the same setting in the settings screen
+#[derive(Clone, Default)]
+pub struct SettingsOverrides {
+    pub shading: Option<Shading>,
+    pub windowed: bool,
+    pub resolution: Option<(u32, u32)>,
+}
+
+impl SettingsOverrides {
+    pub fn shading(&self, graphics: &GraphicsSettings) -> Shading {
+        self.shading.unwrap_or(graphics.shading)
+    }
+
+    // the display settings the window is set up with
+    pub fn display(&self, display: &DisplaySettings) -> DisplaySettings {
+        let mut display = display.clone();
+        if self.windowed {
+            display.mode = DisplayMode::Windowed;
+        }
+        if let Some(resolution) = self.resolution {
+            display.resolution = Some(resolution);
+        }
+        display
+    }
+
+    pub fn overrides_display(&self) -> bool {
+        self.windowed || self.resolution.is_some()
+    }
+}
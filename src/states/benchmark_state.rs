@@ -0,0 +1,453 @@
+use crate::{
+    assets::{AssetCache, AssetProgress},
+    ecs::CurrentState,
+    logger::prelude::*,
+    profiler::Profiler,
+    scene::{self, SceneHandle},
+};
+use amethyst::{
+    assets::Completion,
+    core::{
+        nalgebra::{Translation3, UnitQuaternion, Vector3},
+        specs::prelude::*,
+        timing::Time,
+        Transform,
+    },
+    prelude::*,
+    renderer::Camera,
+};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+// a point the camera passes through, looking at the target
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Waypoint {
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BenchmarkConfig {
+    pub scene: String,
+    // in seconds, the frames of the warmup aren't measured
+    pub warmup: f32,
+    pub duration: f32,
+    // the camera follows a smooth curve through the waypoints
+    pub waypoints: Vec<Waypoint>,
+    // the last waypoint connects back to the first
+    pub looped: bool,
+    // frames taking longer than this many milliseconds are reported as stutters
+    pub stutter_threshold: f32,
+    pub report: PathBuf,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        BenchmarkConfig {
+            scene: "gameplay".into(),
+            warmup: 2.0,
+            duration: 30.0,
+            waypoints: Vec::new(),
+            looped: false,
+            stutter_threshold: 33.3,
+            report: "benchmark_report.txt".into(),
+        }
+    }
+}
+
+// Plays back a camera flight through a scene, measures the frame times and quits.
+// Started instead of the main menu when the game is run with --benchmark
+pub struct BenchmarkState {
+    config: BenchmarkConfig,
+    progress: AssetProgress,
+    scene: Option<SceneHandle>,
+    scene_entity: Option<Entity>,
+    elapsed: f32,
+    // in milliseconds
+    frame_times: Vec<f32>,
+}
+
+impl BenchmarkState {
+    pub fn new(config: BenchmarkConfig) -> BenchmarkState {
+        BenchmarkState {
+            config,
+            progress: AssetProgress::new(),
+            scene: None,
+            scene_entity: None,
+            elapsed: 0.0,
+            frame_times: Vec::new(),
+        }
+    }
+
+    fn move_camera(&self, world: &World) {
+        let fraction = ((self.elapsed - self.config.warmup) / self.config.duration)
+            .max(0.0)
+            .min(1.0);
+        let (position, target) =
+            match sample_path(&self.config.waypoints, self.config.looped, fraction) {
+                Some(point) => point,
+                None => return,
+            };
+
+        let direction = target - position;
+        if direction.norm() < 1.0e-6 {
+            return;
+        }
+        // cameras look along their negative z axis
+        let rotation = UnitQuaternion::new_observer_frame(&-direction, &Vector3::y());
+
+        let cameras = world.read_storage::<Camera>();
+        let mut transforms = world.write_storage::<Transform>();
+        for (transform, _) in (&mut transforms, &cameras).join() {
+            *transform = Transform::new(
+                Translation3::new(position.x, position.y, position.z),
+                rotation,
+                Vector3::new(1.0, 1.0, 1.0),
+            );
+        }
+    }
+
+    fn write_report(&self) -> io::Result<()> {
+        let report = Report::new(&self.frame_times, self.config.stutter_threshold);
+        let mut file = fs::File::create(&self.config.report)?;
+
+        writeln!(file, "smc {} benchmark", env!("CARGO_PKG_VERSION"))?;
+        writeln!(file, "date: {}", Local::now().format("%Y-%m-%d %H:%M:%S"))?;
+        writeln!(file, "scene: {}", self.config.scene)?;
+        writeln!(file, "duration: {} s", self.config.duration)?;
+        writeln!(file)?;
+        report.write(&mut file)
+    }
+}
+
+impl SimpleState for BenchmarkState {
+    fn on_start(&mut self, StateData { world, .. }: StateData<GameData>) {
+        *world.write_resource::<CurrentState>() = CurrentState::Benchmark;
+        info!("Starting benchmark of scene {}", self.config.scene);
+
+        self.scene = Some(scene::load(
+            world,
+            "benchmark",
+            &self.config.scene,
+            &mut self.progress,
+        ));
+    }
+
+    fn on_stop(&mut self, StateData { world, .. }: StateData<GameData>) {
+        if let Some(scene) = self.scene_entity {
+            world.delete_entity(scene).unwrap();
+        }
+        world.write_resource::<AssetCache>().release("benchmark");
+    }
+
+    fn update(&mut self, StateData { world, .. }: &mut StateData<GameData>) -> SimpleTrans {
+        let profiler = world.read_resource::<Profiler>().clone();
+        let _scope = profiler.scope("benchmark_state");
+
+        if self.scene_entity.is_none() {
+            match self.progress.complete() {
+                Completion::Complete => {
+                    self.scene_entity = Some(scene::spawn(world, self.scene.as_ref().unwrap()));
+                }
+                Completion::Loading => (),
+                Completion::Failed => {
+                    for (asset, err) in self.progress.errors() {
+                        error!("Failed to load {}: {}", asset, err);
+                    }
+                    return Trans::Quit;
+                }
+            }
+            return Trans::None;
+        }
+
+        let delta = world.read_resource::<Time>().delta_real_seconds();
+        self.elapsed += delta;
+        if self.elapsed > self.config.warmup {
+            self.frame_times.push(delta * 1000.0);
+        }
+        self.move_camera(world);
+
+        if self.elapsed < self.config.warmup + self.config.duration {
+            return Trans::None;
+        }
+
+        match self.write_report() {
+            Ok(()) => info!("Wrote benchmark report to {}", self.config.report.display()),
+            Err(err) => error!("Failed to write benchmark report: {}", err),
+        }
+        Trans::Quit
+    }
+}
+
+// the position and target at the fraction of the path, through a Catmull-Rom spline
+fn sample_path(
+    waypoints: &[Waypoint],
+    looped: bool,
+    fraction: f32,
+) -> Option<(Vector3<f32>, Vector3<f32>)> {
+    let count = waypoints.len();
+    if count == 0 {
+        return None;
+    }
+
+    let segments = if looped { count } else { count - 1 };
+    if segments == 0 {
+        let waypoint = &waypoints[0];
+        return Some((vector(waypoint.position), vector(waypoint.target)));
+    }
+
+    let along = fraction * segments as f32;
+    let segment = (along.floor() as usize).min(segments - 1);
+    let t = along - segment as f32;
+
+    // neighbours past the ends repeat the end points unless the path is looped
+    let point = |index: isize| {
+        let index = if looped {
+            (index + count as isize) % count as isize
+        } else {
+            index.max(0).min(count as isize - 1)
+        };
+        &waypoints[index as usize]
+    };
+    let points = [
+        point(segment as isize - 1),
+        point(segment as isize),
+        point(segment as isize + 1),
+        point(segment as isize + 2),
+    ];
+    let spline = |get: fn(&Waypoint) -> [f32; 3]| {
+        catmull_rom(
+            [
+                vector(get(points[0])),
+                vector(get(points[1])),
+                vector(get(points[2])),
+                vector(get(points[3])),
+            ],
+            t,
+        )
+    };
+
+    Some((
+        spline(|waypoint| waypoint.position),
+        spline(|waypoint| waypoint.target),
+    ))
+}
+
+fn vector([x, y, z]: [f32; 3]) -> Vector3<f32> {
+    Vector3::new(x, y, z)
+}
+
+fn catmull_rom([p0, p1, p2, p3]: [Vector3<f32>; 4], t: f32) -> Vector3<f32> {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+// frame time statistics, in milliseconds
+struct Report {
+    frames: usize,
+    average: f32,
+    min: f32,
+    max: f32,
+    percentiles: Vec<(f32, f32)>,
+    stutter_threshold: f32,
+    stutters: usize,
+}
+
+impl Report {
+    fn new(frame_times: &[f32], stutter_threshold: f32) -> Report {
+        // a NaN can't be ordered, so it's left out
+        let mut sorted: Vec<f32> = frame_times
+            .iter()
+            .cloned()
+            .filter(|time| !time.is_nan())
+            .collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let frames = sorted.len();
+        let total: f32 = sorted.iter().sum();
+        let percentile = |percent: f32| {
+            if frames == 0 {
+                return 0.0;
+            }
+            let index = ((percent / 100.0) * (frames - 1) as f32).round() as usize;
+            sorted[index]
+        };
+
+        Report {
+            frames,
+            average: if frames > 0 {
+                total / frames as f32
+            } else {
+                0.0
+            },
+            min: sorted.first().cloned().unwrap_or(0.0),
+            max: sorted.last().cloned().unwrap_or(0.0),
+            percentiles: [50.0, 90.0, 95.0, 99.0, 99.9]
+                .iter()
+                .map(|&percent| (percent, percentile(percent)))
+                .collect(),
+            stutter_threshold,
+            stutters: sorted
+                .iter()
+                .filter(|&&time| time > stutter_threshold)
+                .count(),
+        }
+    }
+
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let fps = if self.average > 0.0 {
+            1000.0 / self.average
+        } else {
+            0.0
+        };
+
+        writeln!(out, "frames: {}", self.frames)?;
+        writeln!(out, "average: {:.3} ms ({:.1} FPS)", self.average, fps)?;
+        writeln!(out, "min: {:.3} ms", self.min)?;
+        writeln!(out, "max: {:.3} ms", self.max)?;
+        for (percent, time) in &self.percentiles {
+            writeln!(out, "p{}: {:.3} ms", percent, time)?;
+        }
+        writeln!(
+            out,
+            "stutters over {} ms: {}",
+            self.stutter_threshold, self.stutters
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: Vector3<f32>, b: [f32; 3]) -> bool {
+        (a - vector(b)).norm() < 1.0e-5
+    }
+
+    // looks straight down from above each point
+    fn waypoints(points: &[[f32; 3]]) -> Vec<Waypoint> {
+        points
+            .iter()
+            .map(|&[x, y, z]| Waypoint {
+                position: [x, y + 1.0, z],
+                target: [x, y, z],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sample_path_empty() {
+        assert!(sample_path(&[], false, 0.5).is_none());
+    }
+
+    #[test]
+    fn sample_path_single_waypoint() {
+        let path = waypoints(&[[1.0, 2.0, 3.0]]);
+        for &looped in &[false, true] {
+            let (position, target) = sample_path(&path, looped, 0.7).unwrap();
+            assert!(approx(position, [1.0, 3.0, 3.0]));
+            assert!(approx(target, [1.0, 2.0, 3.0]));
+        }
+    }
+
+    #[test]
+    fn sample_path_ends() {
+        let path = waypoints(&[[0.0, 0.0, 0.0], [2.0, 0.0, 1.0], [4.0, 0.0, 0.0]]);
+
+        let (position, target) = sample_path(&path, false, 0.0).unwrap();
+        assert!(approx(position, [0.0, 1.0, 0.0]));
+        assert!(approx(target, [0.0, 0.0, 0.0]));
+
+        let (position, target) = sample_path(&path, false, 1.0).unwrap();
+        assert!(approx(position, [4.0, 1.0, 0.0]));
+        assert!(approx(target, [4.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn sample_path_passes_through_waypoints() {
+        let path = waypoints(&[[0.0, 0.0, 0.0], [2.0, 0.0, 1.0], [4.0, 0.0, 0.0]]);
+        let (position, _) = sample_path(&path, false, 0.5).unwrap();
+        assert!(approx(position, [2.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn sample_path_evenly_spaced_line() {
+        let path = waypoints(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [3.0, 0.0, 0.0],
+        ]);
+        let (position, target) = sample_path(&path, false, 0.5).unwrap();
+        assert!(approx(position, [1.5, 1.0, 0.0]));
+        assert!(approx(target, [1.5, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn sample_path_looped() {
+        let path = waypoints(&[[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [2.0, 0.0, 2.0]]);
+
+        // the last segment leads back to the first waypoint
+        let (position, _) = sample_path(&path, true, 1.0).unwrap();
+        assert!(approx(position, [0.0, 1.0, 0.0]));
+        let (position, _) = sample_path(&path, true, 2.0 / 3.0).unwrap();
+        assert!(approx(position, [2.0, 1.0, 2.0]));
+    }
+
+    #[test]
+    fn report_percentiles() {
+        let frame_times: Vec<f32> = (1..=100).rev().map(|time| time as f32).collect();
+        let report = Report::new(&frame_times, 33.3);
+
+        assert_eq!(report.frames, 100);
+        assert_eq!(report.average, 50.5);
+        assert_eq!(report.min, 1.0);
+        assert_eq!(report.max, 100.0);
+        assert_eq!(
+            report.percentiles,
+            vec![
+                (50.0, 51.0),
+                (90.0, 90.0),
+                (95.0, 95.0),
+                (99.0, 99.0),
+                (99.9, 100.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn report_stutters() {
+        let frame_times = [10.0, 16.7, 33.3, 33.4, 50.0];
+        assert_eq!(Report::new(&frame_times, 33.3).stutters, 2);
+        assert_eq!(Report::new(&frame_times, 100.0).stutters, 0);
+    }
+
+    #[test]
+    fn report_no_frames() {
+        let report = Report::new(&[], 33.3);
+        assert_eq!(report.frames, 0);
+        assert_eq!(report.average, 0.0);
+        assert_eq!((report.min, report.max), (0.0, 0.0));
+        assert!(report.percentiles.iter().all(|&(_, time)| time == 0.0));
+    }
+
+    #[test]
+    fn report_ignores_nan() {
+        let report = Report::new(&[2.0, std::f32::NAN, 1.0], 33.3);
+        assert_eq!(report.frames, 2);
+        assert_eq!((report.min, report.max), (1.0, 2.0));
+    }
+}
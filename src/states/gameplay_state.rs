@@ -1,63 +1,29 @@
-use crate::{ecs::CurrentState, graphics};
+use crate::{
+    ecs::CurrentState,
+    profiler::Profiler,
+    scene::{self, SceneHandle},
+};
 use amethyst::{
-    assets::{AssetLoaderSystemData, Handle, ProgressCounter},
     controls::HideCursor,
-    core::{
-        nalgebra::{Quaternion, Translation3, UnitQuaternion, Vector3},
-        shrev::EventChannel,
-        specs::prelude::*,
-        Transform,
-    },
+    core::{shrev::EventChannel, specs::prelude::*},
     input::InputEvent,
     prelude::*,
-    renderer::{Material, MaterialDefaults, Mesh, ObjFormat, Texture},
 };
 
-#[derive(Clone)]
-pub struct GameplayData {
-    pub mtl: Material,
-    pub mesh: Handle<Mesh>,
-    pub transform: Transform,
-}
-
-impl GameplayData {
-    pub fn load(world: &mut World, progress_counter: &mut ProgressCounter) -> GameplayData {
-        let material_defaults = world.read_resource::<MaterialDefaults>().0.clone();
-        let material = world.exec(|loader: AssetLoaderSystemData<Texture>| Material {
-            albedo: loader.load_from_data([0.0, 0.0, 1.0, 1.0].into(), &mut *progress_counter),
-            ..material_defaults
-        });
-        let mesh = world.exec(|loader: AssetLoaderSystemData<Mesh>| {
-            loader.load("mesh/suzanne.obj", ObjFormat, (), &mut *progress_counter)
-        });
-        let transform = Transform::default();
-
-        GameplayData {
-            mtl: material,
-            mesh,
-            transform,
-        }
-    }
-}
-
 pub struct GameplayState {
-    data: GameplayData,
+    scene: SceneHandle,
     event_reader: Option<ReaderId<InputEvent<String>>>,
     paused: bool,
-    suzanne: Option<Entity>,
-    light: Option<Entity>,
-    camera: Option<Entity>,
+    scene_entity: Option<Entity>,
 }
 
 impl GameplayState {
-    pub fn new(data: GameplayData) -> GameplayState {
+    pub fn new(scene: SceneHandle) -> GameplayState {
         GameplayState {
-            data,
+            scene,
             event_reader: None,
             paused: false,
-            suzanne: None,
-            light: None,
-            camera: None,
+            scene_entity: None,
         }
     }
 }
@@ -73,34 +39,19 @@ impl SimpleState for GameplayState {
                 .register_reader(),
         );
 
-        self.suzanne = Some(
-            world
-                .create_entity()
-                .with(self.data.mtl.clone())
-                .with(self.data.mesh.clone())
-                .with(self.data.transform.clone())
-                .build(),
-        );
-        self.light = Some(graphics::initialize_light(world));
-        self.camera = Some(graphics::initialize_camera(
-            world,
-            Transform::new(
-                Translation3::new(0.0, 0.0, -4.0),
-                UnitQuaternion::from_quaternion(Quaternion::new(0.0, 0.0, 1.0, 0.0)),
-                Vector3::new(1.0, 1.0, 1.0),
-            ),
-        ));
+        self.scene_entity = Some(scene::spawn(world, &self.scene));
     }
 
     fn on_stop(&mut self, StateData { world, .. }: StateData<GameData>) {
         (*world.write_resource::<HideCursor>()).hide = false;
 
-        world.delete_entity(self.suzanne.unwrap()).unwrap();
-        world.delete_entity(self.light.unwrap()).unwrap();
-        world.delete_entity(self.camera.unwrap()).unwrap();
+        world.delete_entity(self.scene_entity.unwrap()).unwrap();
     }
 
-    fn update(&mut self, _: &mut StateData<GameData>) -> SimpleTrans {
+    fn update(&mut self, StateData { world, .. }: &mut StateData<GameData>) -> SimpleTrans {
+        let profiler = world.read_resource::<Profiler>().clone();
+        let _scope = profiler.scope("gameplay_state");
+
         if self.paused {
             Trans::Pop
         } else {
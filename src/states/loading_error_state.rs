@@ -0,0 +1,98 @@
+use crate::{
+    profiler::Profiler,
+    states::LoadingState,
+    ui::{self, CustomUi, UiBindings},
+};
+use amethyst::{
+    assets::ProgressCounter,
+    audio::AudioFormat,
+    core::specs::Entity,
+    prelude::*,
+    renderer::TextureFormat,
+    ui::{FontFormat, UiCreator, UiText},
+};
+
+// Lists the assets that failed to load and lets the player retry or quit
+pub struct LoadingErrorState {
+    errors: Vec<(String, String)>,
+    error_gui: Option<Entity>,
+    bindings: UiBindings<LoadingErrorState>,
+    // the list is written once its text exists
+    errors_shown: bool,
+}
+
+impl LoadingErrorState {
+    pub fn new(errors: Vec<(String, String)>) -> LoadingErrorState {
+        LoadingErrorState {
+            errors,
+            error_gui: None,
+            bindings: UiBindings::new()
+                .element("error_list")
+                .on_click("retry", |_, _| Trans::Switch(Box::new(LoadingState::new())))
+                .on_click("quit", |_, _| Trans::Quit)
+                .on_back(|_, _| Trans::Quit),
+            errors_shown: false,
+        }
+    }
+}
+
+impl SimpleState for LoadingErrorState {
+    fn on_start(&mut self, StateData { world, .. }: StateData<GameData>) {
+        let error_gui = world.exec(
+            |mut ui_creator: UiCreator<'_, AudioFormat, TextureFormat, FontFormat, CustomUi>| {
+                ui_creator.create("ui/loading_error.ron", &mut ProgressCounter::new())
+            },
+        );
+        self.error_gui = Some(error_gui);
+        self.bindings.attach(error_gui);
+    }
+
+    fn on_stop(&mut self, StateData { world, .. }: StateData<GameData>) {
+        world.delete_entity(self.error_gui.unwrap()).unwrap();
+    }
+
+    fn handle_event(
+        &mut self,
+        StateData { world, .. }: StateData<GameData>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        match self.bindings.handler(&event) {
+            Some(handler) => handler(self, world),
+            None => Trans::None,
+        }
+    }
+
+    fn update(&mut self, StateData { world, .. }: &mut StateData<GameData>) -> SimpleTrans {
+        let profiler = world.read_resource::<Profiler>().clone();
+        let _scope = profiler.scope("loading_error_state");
+
+        if let Some(handler) = self.bindings.back(world) {
+            return handler(self, world);
+        }
+
+        if let Some(error_gui) = ui::reload(world, "ui/loading_error.ron", self.error_gui.unwrap())
+        {
+            self.error_gui = Some(error_gui);
+            self.bindings.attach(error_gui);
+            self.errors_shown = false;
+        }
+
+        if self.errors_shown || !self.bindings.resolve(world) {
+            return Trans::None;
+        }
+
+        if let Some(error_list) = self.bindings.get("error_list") {
+            if let Some(text) = world.write_storage::<UiText>().get_mut(error_list) {
+                text.text = self
+                    .errors
+                    .iter()
+                    .map(|(asset, err)| format!("{}: {}", asset, err))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.errors_shown = true;
+            }
+        }
+
+        Trans::None
+    }
+}
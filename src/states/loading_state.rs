@@ -1,60 +1,200 @@
 use crate::{
+    assets::{AssetCache, AssetProgress, Tips},
     ecs::CurrentState,
+    localization::Localization,
     logger::prelude::*,
-    states::{GameplayData, MainMenuData, MainMenuState},
-    ui::{self, CustomUi},
+    profiler::Profiler,
+    scene::{self, SceneHandle},
+    settings::Settings,
+    states::{BenchmarkConfig, BenchmarkState, LoadingErrorState, MainMenuState},
+    ui::{self, CustomUi, ProgressBar, UiBindings},
 };
 use amethyst::{
-    assets::{Completion, Handle, ProgressCounter},
+    assets::{AssetLoaderSystemData, AssetStorage, Completion, Handle, ProgressCounter, RonFormat},
     audio::{output, AudioFormat},
-    core::{specs::Entity, transform::components::ParentHierarchy},
+    core::{specs::Entity, timing::Time},
     prelude::*,
-    renderer::TextureFormat,
-    ui::{FontFormat, UiCreator, UiFinder, UiLoader, UiPrefab, UiText, UiTransform},
+    renderer::{PngFormat, Texture, TextureFormat, TextureMetadata},
+    ui::{FontFormat, UiCreator, UiImage, UiLoader, UiPrefab, UiText},
 };
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LoadingConfig {
+    // in seconds, so the loading screen doesn't just flash when everything is cached
+    pub min_display_time: f32,
+    pub tip_interval: f32,
+    pub background_interval: f32,
+    pub backgrounds: Vec<String>,
+    // how fast the bar catches up with the real progress, higher is faster
+    pub progress_smoothing: f32,
+}
+
+impl Default for LoadingConfig {
+    fn default() -> Self {
+        LoadingConfig {
+            min_display_time: 1.0,
+            tip_interval: 5.0,
+            background_interval: 8.0,
+            backgrounds: Vec::new(),
+            progress_smoothing: 8.0,
+        }
+    }
+}
 
 pub struct LoadingState {
-    progress_counter: ProgressCounter,
-    gameplay_data: Option<GameplayData>,
-    mainmenu_data: Option<MainMenuData>,
+    config: LoadingConfig,
+    progress: AssetProgress,
+    mainmenu_scene: Option<SceneHandle>,
     mainmenu_gui: Option<Handle<UiPrefab>>,
     loading_gui: Option<Entity>,
-    progress_bar: Option<Entity>,
+    bindings: UiBindings<LoadingState>,
+    tips: Option<Handle<Tips>>,
+    backgrounds: Vec<Handle<Texture>>,
+    elapsed: f32,
+    shown_progress: f32,
+    next_tip: f32,
+    next_background: f32,
+    background_index: usize,
 }
 
 impl LoadingState {
     pub fn new() -> LoadingState {
         LoadingState {
-            progress_counter: ProgressCounter::new(),
-            gameplay_data: None,
-            mainmenu_data: None,
+            config: LoadingConfig::default(),
+            progress: AssetProgress::new(),
+            mainmenu_scene: None,
             mainmenu_gui: None,
             loading_gui: None,
-            progress_bar: None,
+            bindings: UiBindings::new()
+                .element("progress_bar")
+                .element("current_asset")
+                .element("tip"),
+            tips: None,
+            backgrounds: Vec::new(),
+            elapsed: 0.0,
+            shown_progress: 0.0,
+            next_tip: 0.0,
+            next_background: 0.0,
+            background_index: 0,
+        }
+    }
+
+    fn update_progress_bar(&self, world: &World, progress_bar: Entity) {
+        if let Some(progress_bar) = world.write_storage::<ProgressBar>().get_mut(progress_bar) {
+            progress_bar.value = self.shown_progress;
+        }
+    }
+
+    fn update_current_asset(&self, world: &World, current_asset: Entity) {
+        if let Some(text) = world.write_storage::<UiText>().get_mut(current_asset) {
+            text.text = self.progress.current().unwrap_or_default();
+        }
+    }
+
+    fn update_tip(&mut self, world: &World, tip: Entity) {
+        if self.elapsed < self.next_tip {
+            return;
+        }
+
+        let storage = world.read_resource::<AssetStorage<Tips>>();
+        let tips = match self.tips.as_ref().and_then(|handle| storage.get(handle)) {
+            Some(tips) => tips,
+            None => return,
+        };
+
+        if let Some(text) = world.write_storage::<UiText>().get_mut(tip) {
+            text.text = tips
+                .0
+                .choose(&mut rand::thread_rng())
+                .cloned()
+                .unwrap_or_default();
+        }
+        self.next_tip = self.elapsed + self.config.tip_interval;
+    }
+
+    fn update_background(&mut self, world: &World) {
+        if self.backgrounds.is_empty() || self.elapsed < self.next_background {
+            return;
+        }
+
+        let background = &self.backgrounds[self.background_index % self.backgrounds.len()];
+        if world
+            .read_resource::<AssetStorage<Texture>>()
+            .get(background)
+            .is_none()
+        {
+            return;
+        }
+
+        let mut images = world.write_storage::<UiImage>();
+        if let Some(image) = self.loading_gui.and_then(|gui| images.get_mut(gui)) {
+            image.texture = background.clone();
         }
+        self.background_index += 1;
+        self.next_background = self.elapsed + self.config.background_interval;
     }
 }
 
 impl SimpleState for LoadingState {
     fn on_start(&mut self, StateData { world, .. }: StateData<GameData>) {
         *world.write_resource::<CurrentState>() = CurrentState::Loading;
+        self.config = world.read_resource::<LoadingConfig>().clone();
 
-        output::init_output(&mut world.res);
+        if !world.res.has_value::<output::Output>() {
+            output::init_output(&mut world.res);
+        }
 
-        self.mainmenu_gui = Some(world.exec(
-            |ui_loader: UiLoader<'_, AudioFormat, TextureFormat, FontFormat, CustomUi>| {
-                ui_loader.load("ui/mainmenu.ron", &mut self.progress_counter)
-            },
-        ));
+        // the benchmark loads its own scene and never shows the main menu
+        let benchmark = world.res.has_value::<BenchmarkConfig>();
 
-        self.loading_gui = Some(world.exec(
+        let progress = &mut self.progress;
+        if !benchmark {
+            self.mainmenu_gui = Some(world.exec(
+                |ui_loader: UiLoader<'_, AudioFormat, TextureFormat, FontFormat, CustomUi>| {
+                    ui_loader.load("ui/mainmenu.ron", progress.named("ui/mainmenu.ron"))
+                },
+            ));
+        }
+
+        let loading_gui = world.exec(
             |mut ui_creator: UiCreator<'_, AudioFormat, TextureFormat, FontFormat, CustomUi>| {
-                ui_creator.create("ui/loading.ron", &mut self.progress_counter)
+                ui_creator.create("ui/loading.ron", progress.named("ui/loading.ron"))
             },
-        ));
+        );
+        self.loading_gui = Some(loading_gui);
+        self.bindings.attach(loading_gui);
+
+        if !benchmark {
+            self.mainmenu_scene = Some(scene::load(world, "mainmenu", "mainmenu", progress));
+        }
+
+        // only decoration, loading doesn't wait for these
+        let mut decoration = ProgressCounter::new();
+        let tips_path = format!(
+            "text/{}/tips.ron",
+            world.read_resource::<Settings>().language
+        );
+        self.tips = Some(world.exec(|loader: AssetLoaderSystemData<Tips>| {
+            loader.load(tips_path, RonFormat, (), &mut decoration)
+        }));
 
-        self.gameplay_data = Some(GameplayData::load(world, &mut self.progress_counter));
-        self.mainmenu_data = Some(MainMenuData::load(world, &mut self.progress_counter));
+        let backgrounds = &self.config.backgrounds;
+        self.backgrounds = world.exec(|loader: AssetLoaderSystemData<Texture>| {
+            backgrounds
+                .iter()
+                .map(|path| {
+                    loader.load(
+                        path.as_str(),
+                        PngFormat,
+                        TextureMetadata::srgb(),
+                        &mut decoration,
+                    )
+                })
+                .collect()
+        });
     }
 
     fn on_stop(&mut self, StateData { world, .. }: StateData<GameData>) {
@@ -62,52 +202,70 @@ impl SimpleState for LoadingState {
     }
 
     fn update(&mut self, StateData { world, .. }: &mut StateData<GameData>) -> SimpleTrans {
-        match self.progress_counter.complete() {
+        let profiler = world.read_resource::<Profiler>().clone();
+        let _scope = profiler.scope("loading_state");
+
+        let delta = world.read_resource::<Time>().delta_real_seconds();
+        self.elapsed += delta;
+
+        // eases towards the real progress instead of jumping a whole asset at once
+        let smoothing = 1.0 - (-self.config.progress_smoothing * delta).exp();
+        self.shown_progress += (self.progress.fraction() - self.shown_progress) * smoothing;
+
+        if let Some(loading_gui) = ui::reload(world, "ui/loading.ron", self.loading_gui.unwrap()) {
+            self.loading_gui = Some(loading_gui);
+            self.bindings.attach(loading_gui);
+        }
+
+        self.bindings.resolve(world);
+        if let Some(progress_bar) = self.bindings.get("progress_bar") {
+            self.update_progress_bar(world, progress_bar);
+        }
+        if let Some(current_asset) = self.bindings.get("current_asset") {
+            self.update_current_asset(world, current_asset);
+        }
+        if let Some(tip) = self.bindings.get("tip") {
+            self.update_tip(world, tip);
+        }
+        self.update_background(world);
+
+        match self.progress.complete() {
             Completion::Complete => {
+                if self.elapsed < self.config.min_display_time || self.shown_progress < 0.99 {
+                    return Trans::None;
+                }
+
+                // the menus would show their keys until the strings are loaded
+                let language = world.read_resource::<Settings>().language.clone();
+                if !world.read_resource::<Localization>().is_ready(&language) {
+                    return Trans::None;
+                }
+
                 info!("Loading finished");
+                world.read_resource::<AssetCache>().log_usage();
+
+                // only present when started with --benchmark
+                if let Some(config) = world.res.try_fetch::<BenchmarkConfig>() {
+                    return Trans::Switch(Box::new(BenchmarkState::new(config.clone())));
+                }
 
                 Trans::Switch(Box::new(MainMenuState::new(
                     self.mainmenu_gui.as_ref().unwrap().clone(),
-                    self.mainmenu_data.as_ref().unwrap().clone(),
-                    self.gameplay_data.as_ref().unwrap().clone(),
+                    self.mainmenu_scene.as_ref().unwrap().clone(),
                 )))
             }
-            Completion::Loading => {
-                if let Some(progress_bar) = self.progress_bar {
-                    let progress = self.progress_counter.num_finished() as f32
-                        / self.progress_counter.num_assets() as f32;
-
-                    let hierarchy = world.read_resource::<ParentHierarchy>();
-                    let children = hierarchy.children(progress_bar);
-
-                    let mut transforms = world.write_storage::<UiTransform>();
-                    let background_transform = transforms.get(progress_bar).cloned();
-                    let bar_transform = children.get(0).and_then(|&e| transforms.get_mut(e));
-
-                    let mut texts = world.write_storage::<UiText>();
-                    let loading_text = children.get(1).and_then(|&e| texts.get_mut(e));
-
-                    if let (Some(background_transform), Some(bar_transform), Some(loading_text)) =
-                        (background_transform, bar_transform, loading_text)
-                    {
-                        ui::update_loading_bar(
-                            bar_transform,
-                            background_transform,
-                            loading_text,
-                            progress,
-                        );
-                    }
-                } else {
-                    world.exec(|finder: UiFinder| {
-                        if let Some(entity) = finder.find("progress_bar") {
-                            self.progress_bar = Some(entity)
-                        }
-                    });
+            Completion::Loading => Trans::None,
+            Completion::Failed => {
+                let errors = self.progress.errors();
+                for (asset, err) in &errors {
+                    error!("Failed to load {}: {}", asset, err);
                 }
 
-                Trans::None
+                // failed handles must not be handed out again when retrying
+                world.write_resource::<AssetCache>().clear();
+
+                Trans::Switch(Box::new(LoadingErrorState::new(errors)))
             }
-            Completion::Failed => Trans::Quit,
         }
     }
 }
@@ -1,184 +1,215 @@
 use crate::{
-    ecs::{mainmenu::Rotates, CurrentState},
-    graphics,
-    states::{GameplayData, GameplayState},
+    assets::{AssetCache, BackgroundLoad},
+    ecs::CurrentState,
+    localization::{Localization, Localized},
+    logger::prelude::*,
+    profiler::Profiler,
+    scene::{self, SceneHandle},
+    states::{GameplayState, SettingsState},
+    ui::{self, UiBindings},
 };
 use amethyst::{
-    assets::{AssetLoaderSystemData, Handle, ProgressCounter},
-    core::{
-        nalgebra::{Quaternion, Translation3, UnitQuaternion, Vector3},
-        specs::Entity,
-        Transform,
-    },
+    assets::{Completion, Handle},
+    core::{specs::Entity, transform::components::ParentHierarchy},
     prelude::*,
-    renderer::{Material, MaterialDefaults, Mesh, ObjFormat, Texture},
-    ui::{UiEventType, UiFinder, UiPrefab},
+    ui::{MouseReactive, UiPrefab, UiText},
 };
 
-#[derive(Clone)]
-pub struct MainMenuData {
-    pub mtl: Material,
-    pub mesh: Handle<Mesh>,
-    pub transform: Transform,
-}
-
-impl MainMenuData {
-    pub fn load(world: &mut World, progress_counter: &mut ProgressCounter) -> MainMenuData {
-        let material_defaults = world.read_resource::<MaterialDefaults>().0.clone();
-        let material = world.exec(|loader: AssetLoaderSystemData<Texture>| Material {
-            albedo: loader.load_from_data([0.8, 0.1, 0.1, 1.0].into(), &mut *progress_counter),
-            ..material_defaults
-        });
-        let mesh = world.exec(|loader: AssetLoaderSystemData<Mesh>| {
-            loader.load("mesh/suzanne.obj", ObjFormat, (), &mut *progress_counter)
-        });
-        let transform = Transform::default();
-
-        MainMenuData {
-            mtl: material,
-            mesh,
-            transform,
-        }
-    }
-}
-
 pub struct MainMenuState {
-    data: MainMenuData,
+    scene: SceneHandle,
+    scene_entity: Option<Entity>,
     mainmenu_gui: Handle<UiPrefab>,
     mainmenu_gui_entity: Option<Entity>,
-    suzanne: Option<Entity>,
-    light: Option<Entity>,
-    camera: Option<Entity>,
-    gameplay_data: GameplayData,
-    play: Option<Entity>,
-    settings: Option<Entity>,
-    quit: Option<Entity>,
+    gameplay_scene: Option<BackgroundLoad<SceneHandle>>,
+    // the play button, what it shows and the strings it was shown with
+    play_button: Option<(Entity, PlayButton, u32)>,
+    bindings: UiBindings<MainMenuState>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PlayButton {
+    // the percentage of the gameplay scene that is loaded
+    Loading(u32),
+    Ready,
+    Failed,
 }
 
 impl MainMenuState {
-    pub fn new(
-        mainmenu_gui: Handle<UiPrefab>,
-        mainmenu_data: MainMenuData,
-        gameplay_data: GameplayData,
-    ) -> MainMenuState {
+    pub fn new(mainmenu_gui: Handle<UiPrefab>, scene: SceneHandle) -> MainMenuState {
         MainMenuState {
-            data: mainmenu_data,
+            scene,
+            scene_entity: None,
             mainmenu_gui,
             mainmenu_gui_entity: None,
-            suzanne: None,
-            light: None,
-            camera: None,
-            gameplay_data,
-            play: None,
-            settings: None,
-            quit: None,
+            gameplay_scene: None,
+            play_button: None,
+            bindings: UiBindings::new()
+                .on_click("play", MainMenuState::play)
+                .on_click("settings", |_, _| {
+                    Trans::Push(Box::new(SettingsState::new()))
+                })
+                .on_click("quit", |_, _| Trans::Quit),
+        }
+    }
+
+    // the play button is disabled until the gameplay scene is loaded, if that failed it
+    // loads the scene again
+    fn play(&mut self, world: &mut World) -> SimpleTrans {
+        let load = match &mut self.gameplay_scene {
+            Some(load) => load,
+            None => return Trans::None,
+        };
+
+        match load.poll() {
+            Completion::Complete => {
+                Trans::Push(Box::new(GameplayState::new(load.get().unwrap().clone())))
+            }
+            Completion::Loading => Trans::None,
+            Completion::Failed => {
+                // failed handles must not be handed out again when retrying
+                world.write_resource::<AssetCache>().release("gameplay");
+                self.gameplay_scene = Some(load_gameplay(world));
+                Trans::None
+            }
         }
     }
 }
 
+fn load_gameplay(world: &mut World) -> BackgroundLoad<SceneHandle> {
+    BackgroundLoad::start(world, "gameplay", |world, progress| {
+        scene::load(world, "gameplay", "gameplay", progress)
+    })
+}
+
 fn enter(state: &mut MainMenuState, world: &mut World) {
     *world.write_resource::<CurrentState>() = CurrentState::MainMenu;
-    state.mainmenu_gui_entity = Some(
-        world
-            .create_entity()
-            .with(state.mainmenu_gui.clone())
-            .build(),
-    );
-
-    state.suzanne = Some(
-        world
-            .create_entity()
-            .with(Rotates)
-            .with(state.data.mtl.clone())
-            .with(state.data.mesh.clone())
-            .with(state.data.transform.clone())
-            .build(),
-    );
-    state.light = Some(graphics::initialize_light(world));
-    state.camera = Some(graphics::initialize_camera(
-        world,
-        Transform::new(
-            Translation3::new(1.0, 0.0, -4.0),
-            UnitQuaternion::from_quaternion(Quaternion::new(0.0, 0.0, 1.0, 0.0)),
-            Vector3::new(1.0, 1.0, 1.0),
-        ),
-    ));
+    let mainmenu = world
+        .create_entity()
+        .with(state.mainmenu_gui.clone())
+        .build();
+    state.mainmenu_gui_entity = Some(mainmenu);
+    state.bindings.attach(mainmenu);
+
+    state.scene_entity = Some(scene::spawn(world, &state.scene));
 }
 
 impl SimpleState for MainMenuState {
     fn on_start(&mut self, StateData { world, .. }: StateData<GameData>) {
         enter(self, world);
+
+        self.gameplay_scene = Some(load_gameplay(world));
     }
 
     fn on_resume(&mut self, StateData { world, .. }: StateData<GameData>) {
         enter(self, world);
     }
 
+    fn on_stop(&mut self, StateData { world, .. }: StateData<GameData>) {
+        let mut cache = world.write_resource::<AssetCache>();
+        cache.release("mainmenu");
+        cache.release("gameplay");
+    }
+
     fn on_pause(&mut self, StateData { world, .. }: StateData<GameData>) {
         if let Some(mainmenu) = self.mainmenu_gui_entity {
             world.delete_entity(mainmenu).unwrap();
         }
+        self.bindings.detach();
 
-        self.play = None;
-        self.settings = None;
-        self.quit = None;
-
-        world.delete_entity(self.suzanne.unwrap()).unwrap();
-        world.delete_entity(self.light.unwrap()).unwrap();
-        world.delete_entity(self.camera.unwrap()).unwrap();
+        world.delete_entity(self.scene_entity.unwrap()).unwrap();
     }
 
-    fn handle_event(&mut self, _: StateData<GameData>, event: StateEvent) -> SimpleTrans {
-        match event {
-            StateEvent::Ui(ui_event) => {
-                if ui_event.event_type == UiEventType::ClickStop {
-                    if matches(ui_event.target, self.play) {
-                        Trans::Push(Box::new(GameplayState::new(self.gameplay_data.clone())))
-                    } else if matches(ui_event.target, self.settings) {
-                        Trans::None
-                    } else if matches(ui_event.target, self.quit) {
-                        Trans::Quit
-                    } else {
-                        Trans::None
-                    }
-                } else {
-                    Trans::None
-                }
-            }
-            _ => Trans::None,
+    fn handle_event(
+        &mut self,
+        StateData { world, .. }: StateData<GameData>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        match self.bindings.handler(&event) {
+            Some(handler) => handler(self, world),
+            None => Trans::None,
         }
     }
 
     fn update(&mut self, StateData { world, .. }: &mut StateData<GameData>) -> SimpleTrans {
-        if self.play.is_none() {
-            world.exec(|finder: UiFinder| {
-                if let Some(entity) = finder.find("play") {
-                    self.play = Some(entity);
-                }
-            });
-        }
-        if self.settings.is_none() {
-            world.exec(|finder: UiFinder| {
-                if let Some(entity) = finder.find("settings") {
-                    self.settings = Some(entity);
-                }
-            });
-        }
-        if self.quit.is_none() {
-            world.exec(|finder: UiFinder| {
-                if let Some(entity) = finder.find("quit") {
-                    self.quit = Some(entity);
+        let profiler = world.read_resource::<Profiler>().clone();
+        let _scope = profiler.scope("mainmenu_state");
+
+        if let Some(mainmenu) = self.mainmenu_gui_entity {
+            if let Some(mainmenu) = ui::reload(world, "ui/mainmenu.ron", mainmenu) {
+                // the menu is created from the changed prefab again when resuming
+                if let Some(handle) = world.read_storage::<Handle<UiPrefab>>().get(mainmenu) {
+                    self.mainmenu_gui = handle.clone();
                 }
-            });
+                self.mainmenu_gui_entity = Some(mainmenu);
+                self.bindings.attach(mainmenu);
+            }
         }
 
+        self.bindings.resolve(world);
+        update_play_button(self, world);
+
         Trans::None
     }
 }
 
-fn matches(target: Entity, entity: Option<Entity>) -> bool {
-    match (target, entity) {
-        (target, Some(entity)) if target == entity => true,
-        _ => false,
+// disables the play button and shows the progress while the gameplay scene is loading,
+// the button is only changed when what it shows changes
+fn update_play_button(state: &mut MainMenuState, world: &World) {
+    let shown = match &mut state.gameplay_scene {
+        Some(load) => match load.poll() {
+            Completion::Loading => PlayButton::Loading((load.fraction() * 100.0).round() as u32),
+            Completion::Complete => PlayButton::Ready,
+            Completion::Failed => PlayButton::Failed,
+        },
+        None => return,
+    };
+
+    let play = match state.bindings.get("play") {
+        Some(play) => play,
+        None => return,
+    };
+
+    let localization = world.read_resource::<Localization>();
+    let key = (play, shown, localization.generation());
+    if state.play_button == Some(key) {
+        return;
     }
+
+    let hierarchy = world.read_resource::<ParentHierarchy>();
+    let mut texts = world.write_storage::<UiText>();
+    let label = hierarchy
+        .children(play)
+        .iter()
+        .chain(Some(&play))
+        .cloned()
+        .find(|&entity| texts.contains(entity));
+    let label = match label.and_then(|entity| texts.get_mut(entity).map(|text| (entity, text))) {
+        Some(label) => label,
+        None => return,
+    };
+
+    // the text is set here, so the localization must not reset it
+    world.write_storage::<Localized>().remove(label.0);
+    let (text, color) = match shown {
+        PlayButton::Loading(percent) => (
+            format!("{} {}%", localization.get("menu.play"), percent),
+            [0.5, 0.5, 0.5, 1.0],
+        ),
+        PlayButton::Ready => (localization.get("menu.play"), [1.0, 1.0, 1.0, 1.0]),
+        PlayButton::Failed => (localization.get("menu.play.retry"), [0.6, 0.2, 0.2, 1.0]),
+    };
+    label.1.text = text;
+    label.1.color = color;
+
+    // neither clicked nor focused while loading
+    let mut reactive = world.write_storage::<MouseReactive>();
+    if let PlayButton::Loading(_) = shown {
+        reactive.remove(play);
+    } else if !reactive.contains(play) {
+        reactive
+            .insert(play, MouseReactive)
+            .unwrap_log("Failed to enable the play button");
+    }
+
+    state.play_button = Some(key);
 }
@@ -1,7 +1,13 @@
+mod benchmark_state;
 mod gameplay_state;
+mod loading_error_state;
 mod loading_state;
 mod mainmenu_state;
+mod settings_state;
 
+pub use benchmark_state::*;
 pub use gameplay_state::*;
+pub use loading_error_state::*;
 pub use loading_state::*;
 pub use mainmenu_state::*;
+pub use settings_state::*;
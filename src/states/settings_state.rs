@@ -0,0 +1,295 @@
+use crate::{
+    display::Monitors,
+    frame_rate::FPS_OPTIONS,
+    localization::LANGUAGES,
+    profiler::Profiler,
+    settings::{DisplayMode, DisplaySettings, Settings, SettingsOverrides, Shading},
+    ui::{self, CustomUi, Cycle, Slider, Toggle, UiBindings, WidgetEvent, WidgetValue},
+};
+use amethyst::{
+    assets::ProgressCounter,
+    audio::AudioFormat,
+    core::{shrev::EventChannel, specs::prelude::*},
+    prelude::*,
+    renderer::TextureFormat,
+    ui::{FontFormat, UiCreator},
+};
+
+// Changes the settings and saves them when leaving. Most apply right away, the ones
+// marked in the UI as needing a restart only take effect on the next start
+pub struct SettingsState {
+    settings_gui: Option<Entity>,
+    bindings: UiBindings<SettingsState>,
+    event_reader: Option<ReaderId<WidgetEvent>>,
+    initialized: bool,
+    changed: bool,
+    // the options of the resolution widget
+    resolutions: Vec<(u32, u32)>,
+}
+
+impl SettingsState {
+    pub fn new() -> SettingsState {
+        SettingsState {
+            settings_gui: None,
+            bindings: UiBindings::new()
+                .element("language")
+                .element("ui_scale")
+                .element("shading")
+                .element("wireframe")
+                .element("normals")
+                .element("display_mode")
+                .element("resolution")
+                .element("monitor")
+                .element("max_fps")
+                .element("vsync")
+                .on_click("back", |_, _| Trans::Pop)
+                .on_back(|_, _| Trans::Pop),
+            event_reader: None,
+            initialized: false,
+            changed: false,
+            resolutions: Vec::new(),
+        }
+    }
+
+    // shows the current settings in the widgets
+    fn initialize(&mut self, world: &World) {
+        let settings = world.read_resource::<Settings>();
+
+        if let Some(language) = self.bindings.get("language") {
+            if let Some(cycle) = world.write_storage::<Cycle>().get_mut(language) {
+                cycle.options = LANGUAGES
+                    .iter()
+                    .map(|language| format!("$language.{}", language))
+                    .collect();
+                cycle.index = LANGUAGES
+                    .iter()
+                    .position(|&language| language == settings.language)
+                    .unwrap_or(0);
+            }
+        }
+
+        if let Some(ui_scale) = self.bindings.get("ui_scale") {
+            if let Some(slider) = world.write_storage::<Slider>().get_mut(ui_scale) {
+                slider.value = settings.ui_scale;
+            }
+        }
+
+        if let Some(shading) = self.bindings.get("shading") {
+            if let Some(cycle) = world.write_storage::<Cycle>().get_mut(shading) {
+                cycle.options = Shading::ALL
+                    .iter()
+                    .map(|shading| format!("$settings.shading.{}", shading.name()))
+                    .collect();
+                cycle.index = Shading::ALL
+                    .iter()
+                    .position(|&shading| shading == settings.graphics.shading)
+                    .unwrap_or(0);
+            }
+        }
+
+        if let Some(max_fps) = self.bindings.get("max_fps") {
+            if let Some(cycle) = world.write_storage::<Cycle>().get_mut(max_fps) {
+                cycle.options = FPS_OPTIONS
+                    .iter()
+                    .map(|fps| match fps {
+                        Some(fps) => fps.to_string(),
+                        None => "$settings.max_fps.unlimited".into(),
+                    })
+                    .collect();
+                cycle.index = FPS_OPTIONS
+                    .iter()
+                    .position(|&fps| fps == settings.frame_rate.max_fps)
+                    .unwrap_or(0);
+            }
+        }
+
+        let mut toggles = world.write_storage::<Toggle>();
+        for (id, value) in &[
+            ("wireframe", settings.graphics.wireframe),
+            ("normals", settings.graphics.normals),
+            ("vsync", settings.frame_rate.vsync),
+        ] {
+            if let Some(toggle) = self
+                .bindings
+                .get(id)
+                .and_then(|entity| toggles.get_mut(entity))
+            {
+                toggle.value = *value;
+            }
+        }
+
+        if let Some(display_mode) = self.bindings.get("display_mode") {
+            if let Some(cycle) = world.write_storage::<Cycle>().get_mut(display_mode) {
+                cycle.options = DisplayMode::ALL
+                    .iter()
+                    .map(|mode| format!("$settings.display_mode.{}", mode.name()))
+                    .collect();
+                cycle.index = DisplayMode::ALL
+                    .iter()
+                    .position(|&mode| mode == settings.display.mode)
+                    .unwrap_or(0);
+            }
+        }
+
+        if let Some(monitor) = self.bindings.get("monitor") {
+            if let Some(cycle) = world.write_storage::<Cycle>().get_mut(monitor) {
+                let monitors = world.read_resource::<Monitors>();
+                cycle.options = monitors
+                    .0
+                    .iter()
+                    .enumerate()
+                    .map(|(index, monitor)| format!("{}: {}", index + 1, monitor.name))
+                    .collect();
+                if cycle.options.is_empty() {
+                    cycle.options.push("1".into());
+                }
+                cycle.index = settings.display.monitor.min(cycle.options.len() - 1);
+            }
+        }
+
+        self.show_resolutions(world, &settings.display);
+
+        self.initialized = true;
+    }
+
+    // the resolutions depend on the selected monitor
+    fn show_resolutions(&mut self, world: &World, display: &DisplaySettings) {
+        self.resolutions = world
+            .read_resource::<Monitors>()
+            .resolutions(display.monitor, display.resolution);
+
+        if let Some(resolution) = self.bindings.get("resolution") {
+            if let Some(cycle) = world.write_storage::<Cycle>().get_mut(resolution) {
+                cycle.options = self
+                    .resolutions
+                    .iter()
+                    .map(|(width, height)| format!("{} × {}", width, height))
+                    .collect();
+                cycle.index = display
+                    .resolution
+                    .and_then(|current| self.resolutions.iter().position(|&r| r == current))
+                    .unwrap_or(0);
+            }
+        }
+    }
+
+    fn apply_changes(&mut self, world: &World) {
+        let events = world.read_resource::<EventChannel<WidgetEvent>>();
+        let mut settings = world.write_resource::<Settings>();
+        let mut overrides = world.write_resource::<SettingsOverrides>();
+
+        let mut monitor_changed = false;
+        for event in events.read(self.event_reader.as_mut().unwrap()) {
+            match (&*event.id, &event.value) {
+                ("language", WidgetValue::Index(index)) => {
+                    if let Some(language) = LANGUAGES.get(*index) {
+                        settings.language = language.to_string();
+                    }
+                }
+                ("ui_scale", WidgetValue::Number(ui_scale)) => settings.ui_scale = *ui_scale,
+                ("shading", WidgetValue::Index(index)) => {
+                    if let Some(&shading) = Shading::ALL.get(*index) {
+                        settings.graphics.shading = shading;
+                    }
+                }
+                ("wireframe", WidgetValue::Bool(value)) => settings.graphics.wireframe = *value,
+                ("normals", WidgetValue::Bool(value)) => settings.graphics.normals = *value,
+                ("display_mode", WidgetValue::Index(index)) => {
+                    if let Some(&mode) = DisplayMode::ALL.get(*index) {
+                        settings.display.mode = mode;
+                        overrides.windowed = false;
+                    }
+                }
+                ("resolution", WidgetValue::Index(index)) => {
+                    if let Some(&resolution) = self.resolutions.get(*index) {
+                        settings.display.resolution = Some(resolution);
+                        overrides.resolution = None;
+                    }
+                }
+                ("max_fps", WidgetValue::Index(index)) => {
+                    if let Some(&fps) = FPS_OPTIONS.get(*index) {
+                        settings.frame_rate.max_fps = fps;
+                    }
+                }
+                ("vsync", WidgetValue::Bool(value)) => settings.frame_rate.vsync = *value,
+                ("monitor", WidgetValue::Index(index)) => {
+                    settings.display.monitor = *index;
+                    // centered on the new monitor
+                    settings.display.position = None;
+                    monitor_changed = true;
+                }
+                _ => continue,
+            }
+            self.changed = true;
+        }
+
+        if monitor_changed {
+            self.show_resolutions(world, &settings.display);
+        }
+    }
+}
+
+impl SimpleState for SettingsState {
+    fn on_start(&mut self, StateData { world, .. }: StateData<GameData>) {
+        let settings_gui = world.exec(
+            |mut ui_creator: UiCreator<'_, AudioFormat, TextureFormat, FontFormat, CustomUi>| {
+                ui_creator.create("ui/settings.ron", &mut ProgressCounter::new())
+            },
+        );
+        self.settings_gui = Some(settings_gui);
+        self.bindings.attach(settings_gui);
+
+        self.event_reader = Some(
+            world
+                .write_resource::<EventChannel<WidgetEvent>>()
+                .register_reader(),
+        );
+    }
+
+    fn on_stop(&mut self, StateData { world, .. }: StateData<GameData>) {
+        world.delete_entity(self.settings_gui.unwrap()).unwrap();
+
+        if self.changed {
+            world.read_resource::<Settings>().save();
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        StateData { world, .. }: StateData<GameData>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        match self.bindings.handler(&event) {
+            Some(handler) => handler(self, world),
+            None => Trans::None,
+        }
+    }
+
+    fn update(&mut self, StateData { world, .. }: &mut StateData<GameData>) -> SimpleTrans {
+        let profiler = world.read_resource::<Profiler>().clone();
+        let _scope = profiler.scope("settings_state");
+
+        if let Some(handler) = self.bindings.back(world) {
+            return handler(self, world);
+        }
+
+        if let Some(settings_gui) = ui::reload(world, "ui/settings.ron", self.settings_gui.unwrap())
+        {
+            self.settings_gui = Some(settings_gui);
+            self.bindings.attach(settings_gui);
+            // the new widgets show the current settings again
+            self.initialized = false;
+        }
+
+        if !self.bindings.resolve(world) {
+            return Trans::None;
+        }
+        if !self.initialized {
+            self.initialize(world);
+        }
+
+        self.apply_changes(world);
+
+        Trans::None
+    }
+}
@@ -0,0 +1,150 @@
+use super::UiNavigation;
+use crate::logger::prelude::*;
+use amethyst::{
+    core::{specs::prelude::*, transform::components::ParentHierarchy},
+    prelude::*,
+    ui::{UiEventType, UiTransform},
+};
+
+pub type ClickHandler<S> = fn(&mut S, &mut World) -> SimpleTrans;
+
+struct Binding<S> {
+    id: &'static str,
+    entity: Option<Entity>,
+    on_click: Option<ClickHandler<S>>,
+}
+
+// The elements a state uses from its UI, looked up by id once the UI finished loading.
+// Clicks on them are dispatched to the state's handlers
+pub struct UiBindings<S> {
+    root: Option<Entity>,
+    bindings: Vec<Binding<S>>,
+    on_back: Option<ClickHandler<S>>,
+    resolved: bool,
+}
+
+impl<S> Default for UiBindings<S> {
+    fn default() -> Self {
+        UiBindings {
+            root: None,
+            bindings: Vec::new(),
+            on_back: None,
+            resolved: false,
+        }
+    }
+}
+
+impl<S> UiBindings<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // an element that is only looked up
+    pub fn element(mut self, id: &'static str) -> Self {
+        self.bindings.push(Binding {
+            id,
+            entity: None,
+            on_click: None,
+        });
+        self
+    }
+
+    pub fn on_click(mut self, id: &'static str, handler: ClickHandler<S>) -> Self {
+        self.bindings.push(Binding {
+            id,
+            entity: None,
+            on_click: Some(handler),
+        });
+        self
+    }
+
+    // called when the back action is pressed with a keyboard or controller
+    pub fn on_back(mut self, handler: ClickHandler<S>) -> Self {
+        self.on_back = Some(handler);
+        self
+    }
+
+    // looks the elements up in another UI, the old entities are forgotten
+    pub fn attach(&mut self, root: Entity) {
+        self.root = Some(root);
+        self.detach_entities();
+    }
+
+    pub fn detach(&mut self) {
+        self.root = None;
+        self.detach_entities();
+    }
+
+    fn detach_entities(&mut self) {
+        for binding in &mut self.bindings {
+            binding.entity = None;
+        }
+        self.resolved = false;
+    }
+
+    pub fn get(&self, id: &str) -> Option<Entity> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.id == id)
+            .and_then(|binding| binding.entity)
+    }
+
+    // returns true once the elements were looked up, missing ids are logged once
+    pub fn resolve(&mut self, world: &World) -> bool {
+        let root = match self.root {
+            Some(root) if !self.resolved => root,
+            _ => return self.resolved,
+        };
+
+        // the UI is instantiated all at once, so it's done once the root has children
+        let hierarchy = world.read_resource::<ParentHierarchy>();
+        if hierarchy.children(root).is_empty() {
+            return false;
+        }
+
+        let entities = world.entities();
+        let transforms = world.read_storage::<UiTransform>();
+        let mut elements = hierarchy.all_children(root);
+        elements.add(root.id());
+
+        let elements: Vec<_> = (&*entities, &transforms, &elements)
+            .join()
+            .map(|(entity, transform, _)| (transform.id.clone(), entity))
+            .collect();
+
+        for binding in &mut self.bindings {
+            binding.entity = elements
+                .iter()
+                .find(|(id, _)| id == binding.id)
+                .map(|&(_, entity)| entity);
+
+            if binding.entity.is_none() {
+                warn!("UI element {} not found", binding.id);
+            }
+        }
+
+        self.resolved = true;
+        true
+    }
+
+    // the handler for a click on one of the elements
+    pub fn handler(&self, event: &StateEvent) -> Option<ClickHandler<S>> {
+        match event {
+            StateEvent::Ui(ui_event) if ui_event.event_type == UiEventType::ClickStop => self
+                .bindings
+                .iter()
+                .find(|binding| binding.entity == Some(ui_event.target))
+                .and_then(|binding| binding.on_click),
+            _ => None,
+        }
+    }
+
+    // the back handler, if the back action was pressed this frame
+    pub fn back(&self, world: &World) -> Option<ClickHandler<S>> {
+        if self.root.is_some() && world.read_resource::<UiNavigation>().back {
+            self.on_back
+        } else {
+            None
+        }
+    }
+}
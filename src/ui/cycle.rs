@@ -0,0 +1,175 @@
+use super::{
+    widget::{self, WidgetData, WidgetEvent, WidgetValue},
+    CustomUi,
+};
+use crate::localization::Localization;
+use amethyst::{
+    assets::AssetPrefab,
+    audio::AudioFormat,
+    core::{shrev::EventChannel, specs::prelude::*, transform::components::ParentHierarchy},
+    renderer::{TextureFormat, TexturePrefab},
+    ui::{
+        Anchor, FontAsset, FontFormat, UiEvent, UiEventType, UiImageBuilder, UiText, UiTextBuilder,
+        UiTransform, UiTransformBuilder, UiWidget,
+    },
+};
+use serde::Deserialize;
+
+#[derive(Clone, Deserialize)]
+pub struct UiCycle {
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub index: usize,
+    pub background_texture: TexturePrefab<TextureFormat>,
+    pub font: Option<AssetPrefab<FontAsset, FontFormat>>,
+    pub font_color: [f32; 4],
+    pub font_size: f32,
+}
+
+impl UiCycle {
+    pub fn native_widget(
+        self,
+        transform: UiTransformBuilder,
+    ) -> (
+        UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>,
+        WidgetData,
+    ) {
+        let cycle = Cycle {
+            options: self.options,
+            index: self.index,
+        };
+
+        let label = UiWidget::Text {
+            transform: UiTransformBuilder {
+                id: widget::part_id(&transform.id, "label"),
+                z: 1.0,
+                width: transform.width,
+                height: transform.height,
+                anchor: Anchor::Middle,
+                ..Default::default()
+            },
+            text: UiTextBuilder {
+                // filled in by the system, once the options are translated
+                text: String::new(),
+                font_size: self.font_size,
+                color: self.font_color,
+                font: self.font,
+                password: false,
+                align: Some(Anchor::Middle),
+                line_mode: None,
+                editable: None,
+            },
+        };
+
+        let widget = UiWidget::Container {
+            background: Some(UiImageBuilder {
+                image: self.background_texture,
+            }),
+            transform: UiTransformBuilder {
+                mouse_reactive: true,
+                ..transform
+            },
+            children: vec![label],
+        };
+
+        (widget, WidgetData::Cycle(cycle))
+    }
+}
+
+// The value of a cycle button, clicking it selects the next option.
+// Used instead of a dropdown where there are only a few options
+#[derive(Clone)]
+pub struct Cycle {
+    pub options: Vec<String>,
+    pub index: usize,
+}
+
+impl Component for Cycle {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Cycle {
+    pub fn selected(&self) -> Option<&str> {
+        self.options.get(self.index).map(|option| option.as_str())
+    }
+
+    pub fn select(&mut self, option: &str) {
+        if let Some(index) = self.options.iter().position(|o| o == option) {
+            self.index = index;
+        }
+    }
+
+    // options can be "$key"s
+    fn label(&self, localization: &Localization) -> String {
+        format!(
+            "< {} >",
+            localization.text(self.selected().unwrap_or_default())
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct CycleSystem {
+    event_reader: Option<ReaderId<UiEvent>>,
+}
+
+impl<'a> System<'a> for CycleSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EventChannel<UiEvent>>,
+        Write<'a, EventChannel<WidgetEvent>>,
+        ReadExpect<'a, ParentHierarchy>,
+        Read<'a, Localization>,
+        WriteStorage<'a, Cycle>,
+        ReadStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ui_events,
+            mut widget_events,
+            hierarchy,
+            localization,
+            mut cycles,
+            transforms,
+            mut texts,
+        ): Self::SystemData,
+    ) {
+        for event in ui_events.read(self.event_reader.as_mut().unwrap()) {
+            if event.event_type != UiEventType::ClickStop {
+                continue;
+            }
+
+            if let Some(cycle) = cycles.get_mut(event.target) {
+                if cycle.options.is_empty() {
+                    continue;
+                }
+
+                cycle.index = (cycle.index + 1) % cycle.options.len();
+                widget_events.single_write(WidgetEvent {
+                    target: event.target,
+                    id: transforms
+                        .get(event.target)
+                        .map(|t| t.id.clone())
+                        .unwrap_or_default(),
+                    value: WidgetValue::Index(cycle.index),
+                });
+            }
+        }
+
+        for (entity, cycle) in (&entities, &cycles).join() {
+            let label = widget::find_part(entity, "label", &hierarchy, &transforms);
+            if let Some(label) = label.and_then(|label| texts.get_mut(label)) {
+                label.text = cycle.label(&localization);
+            }
+        }
+    }
+}
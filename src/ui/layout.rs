@@ -0,0 +1,349 @@
+use super::{widget::WidgetData, CustomUi};
+use crate::settings::Settings;
+use amethyst::{
+    audio::AudioFormat,
+    core::{specs::prelude::*, transform::components::ParentHierarchy},
+    renderer::{ScreenDimensions, TextureFormat, TexturePrefab},
+    ui::{
+        Anchor, FontFormat, ScaleMode, UiImageBuilder, UiText, UiTransform, UiTransformBuilder,
+        UiWidget,
+    },
+};
+use serde::Deserialize;
+
+#[derive(Clone, Deserialize)]
+pub struct UiLayout {
+    pub design_size: [f32; 2],
+    #[serde(default = "full")]
+    pub width: f32,
+    #[serde(default = "full")]
+    pub height: f32,
+    #[serde(default)]
+    pub safe_area: f32,
+    #[serde(default)]
+    pub background: Option<TexturePrefab<TextureFormat>>,
+}
+
+fn full() -> f32 {
+    1.0
+}
+
+impl UiLayout {
+    pub fn native_widget(
+        self,
+        transform: UiTransformBuilder,
+        children: Vec<UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>>,
+    ) -> (
+        UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>,
+        WidgetData,
+    ) {
+        let [design_width, design_height] = self.design_size;
+        let layout = Layout {
+            design_size: self.design_size,
+            width: self.width,
+            height: self.height,
+            safe_area: self.safe_area,
+            applied: None,
+            design: Vec::new(),
+        };
+
+        let widget = UiWidget::Container {
+            background: self.background.map(|image| UiImageBuilder { image }),
+            transform: UiTransformBuilder {
+                width: design_width,
+                height: design_height,
+                ..transform
+            },
+            children,
+        };
+
+        (widget, WidgetData::Layout(layout))
+    }
+}
+
+// where a layout ends up inside the window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Placement {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    // applied to the positions, sizes and fonts of the children
+    pub scale: f32,
+}
+
+// the size and position the children were designed with
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct DesignTransform {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    font_size: Option<f32>,
+    // given as fractions of the parent, instead of pixels
+    percent: bool,
+}
+
+impl DesignTransform {
+    // percentage children already follow the size of the container, only their
+    // fonts are scaled
+    fn scaled(&self, scale: f32) -> DesignTransform {
+        let size_scale = if self.percent { 1.0 } else { scale };
+
+        DesignTransform {
+            x: self.x * size_scale,
+            y: self.y * size_scale,
+            width: self.width * size_scale,
+            height: self.height * size_scale,
+            font_size: self.font_size.map(|font_size| font_size * scale),
+            percent: self.percent,
+        }
+    }
+}
+
+// Scales a container designed for a fixed size to fit the window.
+// Children in pixels keep their positions and are scaled with the container,
+// children in percent keep their share of it
+#[derive(Clone)]
+pub struct Layout {
+    pub design_size: [f32; 2],
+    // fraction of the safe area the container may use
+    pub width: f32,
+    pub height: f32,
+    // margin around the window edges, as a fraction of the smaller side
+    pub safe_area: f32,
+    applied: Option<([f32; 2], f32)>,
+    design: Vec<(Entity, DesignTransform)>,
+}
+
+impl Component for Layout {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Layout {
+    // places the container inside the window, keeping the design's aspect ratio
+    pub fn place(&self, anchor: &Anchor, window: [f32; 2], ui_scale: f32) -> Placement {
+        let [window_width, window_height] = window;
+        let [design_width, design_height] = self.design_size;
+
+        let margin = window_width.min(window_height) * self.safe_area;
+        let available_width = (window_width - margin * 2.0).max(0.0) * self.width;
+        let available_height = (window_height - margin * 2.0).max(0.0) * self.height;
+
+        let fit = (available_width / design_width).min(available_height / design_height);
+        let scale = (fit * ui_scale).max(0.0);
+        let width = design_width * scale;
+        let height = design_height * scale;
+
+        let (x, y) = anchor_offset(anchor, margin, width, height);
+
+        Placement {
+            x,
+            y,
+            width,
+            height,
+            scale,
+        }
+    }
+}
+
+// keeps a container with the anchor inside the margin
+pub fn anchor_offset(anchor: &Anchor, margin: f32, width: f32, height: f32) -> (f32, f32) {
+    let left = margin + width / 2.0;
+    let bottom = margin + height / 2.0;
+
+    match anchor {
+        Anchor::TopLeft => (left, -bottom),
+        Anchor::TopMiddle => (0.0, -bottom),
+        Anchor::TopRight => (-left, -bottom),
+        Anchor::MiddleLeft => (left, 0.0),
+        Anchor::Middle => (0.0, 0.0),
+        Anchor::MiddleRight => (-left, 0.0),
+        Anchor::BottomLeft => (left, bottom),
+        Anchor::BottomMiddle => (0.0, bottom),
+        Anchor::BottomRight => (-left, bottom),
+    }
+}
+
+// Lays out containers again when the window is resized or the UI scale changes
+pub struct LayoutSystem;
+
+impl<'a> System<'a> for LayoutSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, ScreenDimensions>,
+        Read<'a, Settings>,
+        ReadExpect<'a, ParentHierarchy>,
+        WriteStorage<'a, Layout>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            dimensions,
+            settings,
+            hierarchy,
+            mut layouts,
+            mut transforms,
+            mut texts,
+        ): Self::SystemData,
+    ) {
+        let window = [dimensions.width(), dimensions.height()];
+
+        for (entity, layout) in (&entities, &mut layouts).join() {
+            if layout.applied == Some((window, settings.ui_scale)) {
+                continue;
+            }
+
+            // the children are instantiated together with the layout, but show up
+            // in the hierarchy a frame later
+            if hierarchy.children(entity).is_empty() {
+                continue;
+            }
+
+            if layout.design.is_empty() {
+                for (child, _) in (&*entities, &hierarchy.all_children(entity)).join() {
+                    if let Some(transform) = transforms.get(child) {
+                        layout.design.push((
+                            child,
+                            DesignTransform {
+                                x: transform.local_x,
+                                y: transform.local_y,
+                                width: transform.width,
+                                height: transform.height,
+                                font_size: texts.get(child).map(|text| text.font_size),
+                                percent: transform.scale_mode == ScaleMode::Percent,
+                            },
+                        ));
+                    }
+                }
+            }
+
+            let placement = match transforms.get_mut(entity) {
+                Some(transform) => {
+                    let placement = layout.place(&transform.anchor, window, settings.ui_scale);
+                    transform.local_x = placement.x;
+                    transform.local_y = placement.y;
+                    transform.width = placement.width;
+                    transform.height = placement.height;
+                    placement
+                }
+                None => continue,
+            };
+
+            for (child, design) in &layout.design {
+                let scaled = design.scaled(placement.scale);
+                if let Some(transform) = transforms.get_mut(*child) {
+                    transform.local_x = scaled.x;
+                    transform.local_y = scaled.y;
+                    transform.width = scaled.width;
+                    transform.height = scaled.height;
+                }
+                if let (Some(text), Some(font_size)) = (texts.get_mut(*child), scaled.font_size) {
+                    text.font_size = font_size;
+                }
+            }
+
+            layout.applied = Some((window, settings.ui_scale));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(width: f32, height: f32, safe_area: f32) -> Layout {
+        Layout {
+            design_size: [400.0, 200.0],
+            width,
+            height,
+            safe_area,
+            applied: None,
+            design: Vec::new(),
+        }
+    }
+
+    fn placement(x: f32, y: f32, width: f32, height: f32, scale: f32) -> Placement {
+        Placement {
+            x,
+            y,
+            width,
+            height,
+            scale,
+        }
+    }
+
+    #[test]
+    fn place_anchors() {
+        // a margin of 100, the 800 x 800 left fit a container of 800 x 400
+        let layout = layout(1.0, 1.0, 0.1);
+        let place = |anchor| layout.place(&anchor, [1000.0, 1000.0], 1.0);
+        let at = |x, y| placement(x, y, 800.0, 400.0, 2.0);
+
+        assert_eq!(place(Anchor::TopLeft), at(500.0, -300.0));
+        assert_eq!(place(Anchor::TopMiddle), at(0.0, -300.0));
+        assert_eq!(place(Anchor::TopRight), at(-500.0, -300.0));
+        assert_eq!(place(Anchor::MiddleLeft), at(500.0, 0.0));
+        assert_eq!(place(Anchor::Middle), at(0.0, 0.0));
+        assert_eq!(place(Anchor::MiddleRight), at(-500.0, 0.0));
+        assert_eq!(place(Anchor::BottomLeft), at(500.0, 300.0));
+        assert_eq!(place(Anchor::BottomMiddle), at(0.0, 300.0));
+        assert_eq!(place(Anchor::BottomRight), at(-500.0, 300.0));
+    }
+
+    #[test]
+    fn place_ui_scale() {
+        let placed = layout(1.0, 1.0, 0.1).place(&Anchor::TopLeft, [1000.0, 1000.0], 0.5);
+        assert_eq!(placed, placement(300.0, -200.0, 400.0, 200.0, 1.0));
+    }
+
+    #[test]
+    fn place_small_window() {
+        let placed = layout(1.0, 1.0, 0.0).place(&Anchor::Middle, [200.0, 300.0], 1.0);
+        assert_eq!(placed, placement(0.0, 0.0, 200.0, 100.0, 0.5));
+
+        // the margins take up the whole window
+        let placed = layout(1.0, 1.0, 0.6).place(&Anchor::Middle, [10.0, 10.0], 1.0);
+        assert_eq!(placed.scale, 0.0);
+        assert_eq!(placed.width, 0.0);
+    }
+
+    #[test]
+    fn place_percentage_size() {
+        let placed = layout(0.5, 1.0, 0.0).place(&Anchor::Middle, [1000.0, 1000.0], 1.0);
+        assert_eq!(placed, placement(0.0, 0.0, 500.0, 250.0, 1.25));
+
+        let placed = layout(1.0, 0.1, 0.0).place(&Anchor::Middle, [1000.0, 1000.0], 1.0);
+        assert_eq!(placed, placement(0.0, 0.0, 200.0, 100.0, 0.5));
+    }
+
+    #[test]
+    fn scale_children() {
+        let design = DesignTransform {
+            x: 10.0,
+            y: -20.0,
+            width: 100.0,
+            height: 50.0,
+            font_size: Some(30.0),
+            percent: false,
+        };
+        let scaled = design.scaled(2.0);
+        assert_eq!((scaled.x, scaled.y), (20.0, -40.0));
+        assert_eq!((scaled.width, scaled.height), (200.0, 100.0));
+        assert_eq!(scaled.font_size, Some(60.0));
+
+        let design = DesignTransform {
+            x: 0.1,
+            width: 0.5,
+            percent: true,
+            ..design
+        };
+        let scaled = design.scaled(2.0);
+        assert_eq!((scaled.x, scaled.width), (0.1, 0.5));
+        assert_eq!(scaled.font_size, Some(60.0));
+    }
+}
@@ -1,25 +1,77 @@
-mod loading_bar;
+mod bindings;
+mod cycle;
+mod layout;
+mod navigation;
+mod progress_bar;
+mod scroll_list;
+mod slider;
+mod text_input;
+mod toggle;
+mod widget;
 
+use crate::{ecs::hot_reload::ChangedAssets, logger::prelude::*};
 use amethyst::{
+    assets::ProgressCounter,
     audio::AudioFormat,
+    core::specs::Entity,
+    prelude::*,
     renderer::TextureFormat,
-    ui::{FontFormat, ToNativeWidget, UiTransformBuilder, UiWidget},
+    ui::{FontFormat, ToNativeWidget, UiCreator, UiTransformBuilder, UiWidget},
 };
-use loading_bar::UiLoadingBar;
+use cycle::UiCycle;
+use layout::UiLayout;
+use progress_bar::UiProgressBar;
+use scroll_list::UiScrollList;
 use serde::Deserialize;
+use slider::UiSlider;
+use text_input::UiTextInput;
+use toggle::UiToggle;
 
-pub use loading_bar::update_loading_bar;
+pub use bindings::UiBindings;
+pub use cycle::Cycle;
+pub use navigation::UiNavigation;
+pub use progress_bar::{Direction, ProgressBar};
+pub use scroll_list::ScrollList;
+pub use slider::Slider;
+pub use text_input::{TextInput, Validation};
+pub use toggle::Toggle;
+pub use widget::{WidgetBundle, WidgetData, WidgetEvent, WidgetValue};
 
 #[derive(Clone, Deserialize)]
 pub enum CustomUi {
-    LoadingBar {
+    Layout {
         transform: UiTransformBuilder,
-        loading_bar: UiLoadingBar,
+        layout: UiLayout,
+        children: Vec<UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>>,
+    },
+    ProgressBar {
+        transform: UiTransformBuilder,
+        progress_bar: UiProgressBar,
+    },
+    Slider {
+        transform: UiTransformBuilder,
+        slider: UiSlider,
+    },
+    Toggle {
+        transform: UiTransformBuilder,
+        toggle: UiToggle,
+    },
+    Cycle {
+        transform: UiTransformBuilder,
+        cycle: UiCycle,
+    },
+    TextInput {
+        transform: UiTransformBuilder,
+        text_input: UiTextInput,
+    },
+    ScrollList {
+        transform: UiTransformBuilder,
+        scroll_list: UiScrollList,
     },
 }
 
 impl ToNativeWidget for CustomUi {
-    type PrefabData = ();
+    type PrefabData = WidgetData;
 
     fn to_native_widget(
         self,
@@ -29,10 +81,43 @@ impl ToNativeWidget for CustomUi {
         Self::PrefabData,
     ) {
         match self {
-            CustomUi::LoadingBar {
-                loading_bar,
+            CustomUi::Layout {
+                layout,
+                transform,
+                children,
+            } => layout.native_widget(transform, children),
+            CustomUi::ProgressBar {
+                progress_bar,
+                transform,
+            } => progress_bar.native_widget(transform),
+            CustomUi::Slider { slider, transform } => slider.native_widget(transform),
+            CustomUi::Toggle { toggle, transform } => toggle.native_widget(transform),
+            CustomUi::Cycle { cycle, transform } => cycle.native_widget(transform),
+            CustomUi::TextInput {
+                text_input,
+                transform,
+            } => text_input.native_widget(transform),
+            CustomUi::ScrollList {
+                scroll_list,
                 transform,
-            } => (loading_bar.native_widget(transform), ()),
+            } => scroll_list.native_widget(transform),
         }
     }
 }
+
+// recreates the UI from its prefab when it changed on disk during development,
+// returns the new root so the state can attach its bindings to it
+pub fn reload(world: &mut World, path: &str, root: Entity) -> Option<Entity> {
+    if !world.write_resource::<ChangedAssets>().take(path) {
+        return None;
+    }
+
+    world
+        .delete_entity(root)
+        .unwrap_log(format!("Failed to delete {}", path));
+    Some(world.exec(
+        |mut ui_creator: UiCreator<'_, AudioFormat, TextureFormat, FontFormat, CustomUi>| {
+            ui_creator.create(path, &mut ProgressCounter::new())
+        },
+    ))
+}
@@ -0,0 +1,177 @@
+use super::{
+    slider::Slider,
+    widget::{WidgetEvent, WidgetValue},
+};
+use crate::ecs::CurrentState;
+use amethyst::{
+    core::{shrev::EventChannel, specs::prelude::*},
+    input::InputEvent,
+    renderer::Hidden,
+    ui::{MouseReactive, UiEvent, UiEventType, UiTransform},
+};
+use std::cmp::Ordering;
+
+// used as a resource, the element focused with the keyboard or a controller
+#[derive(Default)]
+pub struct UiNavigation {
+    pub focused: Option<Entity>,
+    // the back action was pressed this frame
+    pub back: bool,
+}
+
+// Moves the focus between the mouse reactive elements of menus with the ui_* actions.
+// The focused element is shown through its hover state, activating it sends the same
+// click events as the mouse
+#[derive(Default)]
+pub struct NavigationSystem {
+    event_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+impl NavigationSystem {
+    // the elements in focus order, top to bottom and left to right unless a tab order is set
+    fn focusable(
+        entities: &Entities,
+        transforms: &ReadStorage<UiTransform>,
+        reactive: &ReadStorage<MouseReactive>,
+        hidden: &ReadStorage<Hidden>,
+    ) -> Vec<Entity> {
+        let mut focusable: Vec<_> = (&**entities, transforms, reactive, !hidden)
+            .join()
+            .map(|(entity, transform, _, _)| (entity, transform))
+            .collect();
+
+        focusable.sort_by(|(_, a), (_, b)| {
+            a.tab_order
+                .cmp(&b.tab_order)
+                .then(
+                    b.pixel_y()
+                        .partial_cmp(&a.pixel_y())
+                        .unwrap_or(Ordering::Equal),
+                )
+                .then(
+                    a.pixel_x()
+                        .partial_cmp(&b.pixel_x())
+                        .unwrap_or(Ordering::Equal),
+                )
+        });
+
+        focusable.into_iter().map(|(entity, _)| entity).collect()
+    }
+}
+
+impl<'a> System<'a> for NavigationSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, CurrentState>,
+        Read<'a, EventChannel<InputEvent<String>>>,
+        Write<'a, EventChannel<UiEvent>>,
+        Write<'a, EventChannel<WidgetEvent>>,
+        Write<'a, UiNavigation>,
+        ReadStorage<'a, UiTransform>,
+        ReadStorage<'a, MouseReactive>,
+        ReadStorage<'a, Hidden>,
+        WriteStorage<'a, Slider>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            cur_state,
+            input_events,
+            mut ui_events,
+            mut widget_events,
+            mut navigation,
+            transforms,
+            reactive,
+            hidden,
+            mut sliders,
+        ): Self::SystemData,
+    ) {
+        navigation.back = false;
+
+        let actions: Vec<_> = input_events
+            .read(self.event_reader.as_mut().unwrap())
+            .filter_map(|event| match event {
+                InputEvent::ActionPressed(action) => Some(action.clone()),
+                _ => None,
+            })
+            .collect();
+
+        if *cur_state == CurrentState::Gameplay {
+            navigation.focused = None;
+            return;
+        }
+
+        let focusable = Self::focusable(&entities, &transforms, &reactive, &hidden);
+
+        // the focused element is gone, e.g. because the menu was closed
+        if let Some(focused) = navigation.focused {
+            if !focusable.contains(&focused) {
+                navigation.focused = None;
+            }
+        }
+
+        for action in actions {
+            let index = navigation
+                .focused
+                .and_then(|focused| focusable.iter().position(|&e| e == focused));
+            let slider = navigation
+                .focused
+                .and_then(|focused| sliders.get_mut(focused).map(|s| (focused, s)));
+
+            let moved = match (&*action, slider) {
+                ("ui_left", Some((entity, slider))) | ("ui_right", Some((entity, slider))) => {
+                    let steps = if action == "ui_left" { -1 } else { 1 };
+                    if slider.nudge(steps) {
+                        widget_events.single_write(WidgetEvent {
+                            target: entity,
+                            id: transforms
+                                .get(entity)
+                                .map(|t| t.id.clone())
+                                .unwrap_or_default(),
+                            value: WidgetValue::Number(slider.value),
+                        });
+                    }
+                    None
+                }
+                ("ui_up", _) | ("ui_left", _) => Some(match index {
+                    Some(index) if index > 0 => index - 1,
+                    _ => focusable.len().wrapping_sub(1),
+                }),
+                ("ui_down", _) | ("ui_right", _) | ("ui_next", _) => Some(match index {
+                    Some(index) if index + 1 < focusable.len() => index + 1,
+                    _ => 0,
+                }),
+                ("ui_accept", _) => {
+                    if let Some(focused) = navigation.focused {
+                        ui_events.single_write(UiEvent::new(UiEventType::ClickStart, focused));
+                        ui_events.single_write(UiEvent::new(UiEventType::ClickStop, focused));
+                    }
+                    None
+                }
+                ("ui_back", _) => {
+                    navigation.back = true;
+                    None
+                }
+                _ => None,
+            };
+
+            if let Some(&next) = moved.and_then(|index| focusable.get(index)) {
+                if let Some(previous) = navigation.focused {
+                    ui_events.single_write(UiEvent::new(UiEventType::HoverStop, previous));
+                }
+                ui_events.single_write(UiEvent::new(UiEventType::HoverStart, next));
+                navigation.focused = Some(next);
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use super::{
+    widget::{self, WidgetData},
+    CustomUi,
+};
+use crate::localization::Localization;
+use amethyst::{
+    assets::AssetPrefab,
+    audio::AudioFormat,
+    core::{specs::prelude::*, transform::components::ParentHierarchy},
+    renderer::{TextureFormat, TexturePrefab},
+    ui::{
+        Anchor, FontAsset, FontFormat, UiImageBuilder, UiText, UiTextBuilder, UiTransform,
+        UiTransformBuilder, UiWidget,
+    },
+};
+use serde::Deserialize;
+
+const SPACING: f32 = 10.0;
+
+// the direction the bar grows in
+#[derive(Clone, Copy, Deserialize)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+    BottomToTop,
+    TopToBottom,
+}
+
+impl Default for Direction {
+    fn default() -> Self {
+        Direction::LeftToRight
+    }
+}
+
+impl Direction {
+    fn anchor(self) -> Anchor {
+        match self {
+            Direction::LeftToRight => Anchor::MiddleLeft,
+            Direction::RightToLeft => Anchor::MiddleRight,
+            Direction::BottomToTop => Anchor::BottomMiddle,
+            Direction::TopToBottom => Anchor::TopMiddle,
+        }
+    }
+}
+
+fn default_label() -> String {
+    "{percent}%".into()
+}
+
+#[derive(Clone, Deserialize)]
+pub struct UiProgressBar {
+    pub background_texture: TexturePrefab<TextureFormat>,
+    pub bar_texture: TexturePrefab<TextureFormat>,
+    pub value: f32,
+    #[serde(default)]
+    pub direction: Direction,
+    // "{percent}" and "{value}" are replaced, empty hides the label.
+    // Can be a "$key" of a string that contains them
+    #[serde(default = "default_label")]
+    pub label: String,
+    pub font: Option<AssetPrefab<FontAsset, FontFormat>>,
+    pub font_color: [f32; 4],
+    pub font_size: f32,
+}
+
+impl UiProgressBar {
+    pub fn native_widget(
+        self,
+        transform: UiTransformBuilder,
+    ) -> (
+        UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>,
+        WidgetData,
+    ) {
+        let progress_bar = ProgressBar {
+            value: self.value,
+            direction: self.direction,
+            label: self.label,
+            font_color: self.font_color,
+        };
+
+        let bar = UiWidget::Image {
+            transform: UiTransformBuilder {
+                id: widget::part_id(&transform.id, "bar"),
+                z: 1.0,
+                anchor: self.direction.anchor(),
+                ..Default::default()
+            },
+            image: UiImageBuilder {
+                image: self.bar_texture,
+            },
+        };
+
+        let label = UiWidget::Text {
+            transform: UiTransformBuilder {
+                id: widget::part_id(&transform.id, "label"),
+                z: 2.0,
+                width: transform.width,
+                height: transform.height,
+                anchor: Anchor::Middle,
+                ..Default::default()
+            },
+            text: UiTextBuilder {
+                // filled in by the system, once the label is translated
+                text: String::new(),
+                font_size: self.font_size,
+                color: self.font_color,
+                font: self.font,
+                password: false,
+                align: Some(Anchor::Middle),
+                line_mode: None,
+                editable: None,
+            },
+        };
+
+        let widget = UiWidget::Container {
+            background: Some(UiImageBuilder {
+                image: self.background_texture,
+            }),
+            transform,
+            children: vec![bar, label],
+        };
+
+        (widget, WidgetData::ProgressBar(progress_bar))
+    }
+}
+
+// A bar filled according to its value between 0 and 1, used for loading and health bars.
+// The bar and label are updated by the ProgressBarSystem
+#[derive(Clone)]
+pub struct ProgressBar {
+    pub value: f32,
+    pub direction: Direction,
+    pub label: String,
+    pub font_color: [f32; 4],
+}
+
+impl Component for ProgressBar {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl ProgressBar {
+    pub fn label(&self, localization: &Localization) -> String {
+        let value = self.value.max(0.0).min(1.0);
+
+        localization
+            .text(&self.label)
+            .replace("{percent}", &(value * 100.0).round().to_string())
+            .replace("{value}", &format!("{:.2}", value))
+    }
+}
+
+pub struct ProgressBarSystem;
+
+impl<'a> System<'a> for ProgressBarSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, ParentHierarchy>,
+        Read<'a, Localization>,
+        ReadStorage<'a, ProgressBar>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            hierarchy,
+            localization,
+            progress_bars,
+            mut transforms,
+            mut texts,
+        ): Self::SystemData,
+    ) {
+        for (entity, progress_bar) in (&entities, &progress_bars).join() {
+            let (width, height) = match transforms.get(entity) {
+                Some(transform) => (transform.width - SPACING, transform.height - SPACING),
+                None => continue,
+            };
+            let value = progress_bar.value.max(0.0).min(1.0);
+
+            let bar = widget::find_part(entity, "bar", &hierarchy, &transforms);
+            if let Some(bar) = bar.and_then(|bar| transforms.get_mut(bar)) {
+                let offset = SPACING / 2.0;
+
+                match progress_bar.direction {
+                    Direction::LeftToRight | Direction::RightToLeft => {
+                        bar.width = width * value;
+                        bar.height = height;
+                        bar.local_x = offset + bar.width / 2.0;
+                        bar.local_y = 0.0;
+                    }
+                    Direction::BottomToTop | Direction::TopToBottom => {
+                        bar.width = width;
+                        bar.height = height * value;
+                        bar.local_x = 0.0;
+                        bar.local_y = offset + bar.height / 2.0;
+                    }
+                }
+
+                match progress_bar.direction {
+                    Direction::RightToLeft => bar.local_x = -bar.local_x,
+                    Direction::TopToBottom => bar.local_y = -bar.local_y,
+                    _ => (),
+                }
+            }
+
+            let label = widget::find_part(entity, "label", &hierarchy, &transforms);
+            if let Some(label) = label.and_then(|label| texts.get_mut(label)) {
+                label.text = progress_bar.label(&localization);
+                label.color = progress_bar.font_color;
+            }
+        }
+    }
+}
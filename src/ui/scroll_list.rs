@@ -0,0 +1,256 @@
+use super::{
+    widget::{self, WidgetData, WidgetEvent, WidgetValue},
+    CustomUi,
+};
+use amethyst::{
+    assets::AssetPrefab,
+    audio::AudioFormat,
+    core::{shrev::EventChannel, specs::prelude::*, transform::components::ParentHierarchy},
+    input::{InputEvent, ScrollDirection},
+    renderer::{TextureFormat, TexturePrefab},
+    ui::{
+        Anchor, FontAsset, FontFormat, UiEvent, UiEventType, UiImageBuilder, UiText, UiTextBuilder,
+        UiTransform, UiTransformBuilder, UiWidget,
+    },
+};
+use serde::Deserialize;
+
+const SPACING: f32 = 10.0;
+
+#[derive(Clone, Deserialize)]
+pub struct UiScrollList {
+    #[serde(default)]
+    pub items: Vec<String>,
+    #[serde(default)]
+    pub selected: Option<usize>,
+    // number of visible rows, the widget's height is split between them
+    pub rows: usize,
+    pub background_texture: TexturePrefab<TextureFormat>,
+    pub font: Option<AssetPrefab<FontAsset, FontFormat>>,
+    pub font_color: [f32; 4],
+    pub selected_color: [f32; 4],
+    pub font_size: f32,
+}
+
+impl UiScrollList {
+    pub fn native_widget(
+        self,
+        transform: UiTransformBuilder,
+    ) -> (
+        UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>,
+        WidgetData,
+    ) {
+        let row_height = transform.height / self.rows.max(1) as f32;
+
+        let rows = (0..self.rows)
+            .map(|row| UiWidget::Text {
+                transform: UiTransformBuilder {
+                    id: widget::part_id(&transform.id, &row_part(row)),
+                    y: -(row as f32 + 0.5) * row_height,
+                    z: 1.0,
+                    width: transform.width - SPACING * 2.0,
+                    height: row_height,
+                    anchor: Anchor::TopMiddle,
+                    mouse_reactive: true,
+                    ..Default::default()
+                },
+                text: UiTextBuilder {
+                    text: self.items.get(row).cloned().unwrap_or_default(),
+                    font_size: self.font_size,
+                    color: self.font_color,
+                    font: self.font.clone(),
+                    password: false,
+                    align: Some(Anchor::MiddleLeft),
+                    line_mode: None,
+                    editable: None,
+                },
+            })
+            .collect();
+
+        let widget = UiWidget::Container {
+            background: Some(UiImageBuilder {
+                image: self.background_texture,
+            }),
+            transform: UiTransformBuilder {
+                mouse_reactive: true,
+                ..transform
+            },
+            children: rows,
+        };
+
+        let scroll_list = ScrollList {
+            items: self.items,
+            selected: self.selected,
+            offset: 0,
+            rows: self.rows,
+            font_color: self.font_color,
+            selected_color: self.selected_color,
+            hovered: false,
+        };
+
+        (widget, WidgetData::ScrollList(scroll_list))
+    }
+}
+
+fn row_part(row: usize) -> String {
+    format!("row{}", row)
+}
+
+// The items of a scrollable list, scrolled with the mouse wheel while hovered
+// and selected by clicking
+#[derive(Clone)]
+pub struct ScrollList {
+    items: Vec<String>,
+    pub selected: Option<usize>,
+    offset: usize,
+    rows: usize,
+    font_color: [f32; 4],
+    selected_color: [f32; 4],
+    hovered: bool,
+}
+
+impl Component for ScrollList {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl ScrollList {
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.selected = self
+            .selected
+            .filter(|&selected| selected < self.items.len());
+        self.scroll(0);
+    }
+
+    fn scroll(&mut self, rows: isize) {
+        let max_offset = self.items.len().saturating_sub(self.rows) as isize;
+        self.offset = (self.offset as isize + rows).max(0).min(max_offset) as usize;
+    }
+}
+
+// the list and, for a row, the row the entity belongs to
+fn find_list(
+    entity: Entity,
+    entities: &Entities,
+    hierarchy: &ParentHierarchy,
+    lists: &WriteStorage<ScrollList>,
+    transforms: &ReadStorage<UiTransform>,
+) -> Option<(Entity, Option<usize>)> {
+    if lists.contains(entity) {
+        return Some((entity, None));
+    }
+
+    (&**entities, lists).join().find_map(|(list_entity, list)| {
+        (0..list.rows)
+            .find(|&row| {
+                widget::find_part(list_entity, &row_part(row), hierarchy, transforms)
+                    == Some(entity)
+            })
+            .map(|row| (list_entity, Some(row)))
+    })
+}
+
+#[derive(Default)]
+pub struct ScrollListSystem {
+    ui_reader: Option<ReaderId<UiEvent>>,
+    input_reader: Option<ReaderId<InputEvent<String>>>,
+}
+
+impl<'a> System<'a> for ScrollListSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EventChannel<UiEvent>>,
+        Read<'a, EventChannel<InputEvent<String>>>,
+        Write<'a, EventChannel<WidgetEvent>>,
+        ReadExpect<'a, ParentHierarchy>,
+        WriteStorage<'a, ScrollList>,
+        ReadStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.ui_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+        self.input_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<String>>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ui_events,
+            input_events,
+            mut widget_events,
+            hierarchy,
+            mut lists,
+            transforms,
+            mut texts,
+        ): Self::SystemData,
+    ) {
+        for event in ui_events.read(self.ui_reader.as_mut().unwrap()) {
+            let found = find_list(event.target, &entities, &hierarchy, &lists, &transforms);
+            let (list_entity, row) = match found {
+                Some(found) => found,
+                None => continue,
+            };
+            let list = lists.get_mut(list_entity).unwrap();
+
+            match event.event_type {
+                UiEventType::HoverStart => list.hovered = true,
+                UiEventType::HoverStop => list.hovered = false,
+                UiEventType::ClickStop => {
+                    let index = match row {
+                        Some(row) => list.offset + row,
+                        None => continue,
+                    };
+                    if index < list.items.len() && list.selected != Some(index) {
+                        list.selected = Some(index);
+                        widget_events.single_write(WidgetEvent {
+                            target: list_entity,
+                            id: transforms
+                                .get(list_entity)
+                                .map(|t| t.id.clone())
+                                .unwrap_or_default(),
+                            value: WidgetValue::Index(index),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        for event in input_events.read(self.input_reader.as_mut().unwrap()) {
+            let rows = match *event {
+                InputEvent::MouseWheelMoved(ScrollDirection::ScrollUp) => -1,
+                InputEvent::MouseWheelMoved(ScrollDirection::ScrollDown) => 1,
+                _ => continue,
+            };
+
+            for list in (&mut lists).join().filter(|list| list.hovered) {
+                list.scroll(rows);
+            }
+        }
+
+        for (entity, list) in (&entities, &lists).join() {
+            for row in 0..list.rows {
+                let index = list.offset + row;
+                let text = widget::find_part(entity, &row_part(row), &hierarchy, &transforms);
+                if let Some(text) = text.and_then(|text| texts.get_mut(text)) {
+                    text.text = list.items.get(index).cloned().unwrap_or_default();
+                    text.color = if list.selected == Some(index) {
+                        list.selected_color
+                    } else {
+                        list.font_color
+                    };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use super::{
+    widget::{self, WidgetData, WidgetEvent, WidgetValue},
+    CustomUi,
+};
+use amethyst::{
+    assets::AssetPrefab,
+    audio::AudioFormat,
+    core::{shrev::EventChannel, specs::prelude::*, transform::components::ParentHierarchy},
+    input::InputHandler,
+    renderer::{MouseButton, TextureFormat, TexturePrefab},
+    ui::{
+        Anchor, FontAsset, FontFormat, UiEvent, UiEventType, UiImageBuilder, UiText, UiTextBuilder,
+        UiTransform, UiTransformBuilder, UiWidget,
+    },
+};
+use serde::Deserialize;
+
+#[derive(Clone, Deserialize)]
+pub struct UiSlider {
+    pub min: f32,
+    pub max: f32,
+    // 0 allows any value
+    #[serde(default)]
+    pub step: f32,
+    pub value: f32,
+    #[serde(default)]
+    pub decimals: usize,
+    pub track_texture: TexturePrefab<TextureFormat>,
+    pub fill_texture: TexturePrefab<TextureFormat>,
+    pub thumb_texture: TexturePrefab<TextureFormat>,
+    pub thumb_width: f32,
+    pub font: Option<AssetPrefab<FontAsset, FontFormat>>,
+    pub font_color: [f32; 4],
+    pub font_size: f32,
+}
+
+impl UiSlider {
+    pub fn native_widget(
+        self,
+        transform: UiTransformBuilder,
+    ) -> (
+        UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>,
+        WidgetData,
+    ) {
+        let slider = Slider {
+            min: self.min,
+            max: self.max,
+            step: self.step,
+            value: self.value,
+            decimals: self.decimals,
+            dragging: false,
+        };
+        let fraction = slider.fraction();
+
+        let fill = UiWidget::Image {
+            transform: UiTransformBuilder {
+                id: widget::part_id(&transform.id, "fill"),
+                x: transform.width * fraction / 2.0,
+                z: 1.0,
+                width: transform.width * fraction,
+                height: transform.height,
+                anchor: Anchor::MiddleLeft,
+                ..Default::default()
+            },
+            image: UiImageBuilder {
+                image: self.fill_texture,
+            },
+        };
+
+        let thumb = UiWidget::Image {
+            transform: UiTransformBuilder {
+                id: widget::part_id(&transform.id, "thumb"),
+                x: transform.width * fraction,
+                z: 2.0,
+                width: self.thumb_width,
+                height: transform.height,
+                anchor: Anchor::MiddleLeft,
+                ..Default::default()
+            },
+            image: UiImageBuilder {
+                image: self.thumb_texture,
+            },
+        };
+
+        let label = UiWidget::Text {
+            transform: UiTransformBuilder {
+                id: widget::part_id(&transform.id, "label"),
+                z: 3.0,
+                width: transform.width,
+                height: transform.height,
+                anchor: Anchor::Middle,
+                ..Default::default()
+            },
+            text: UiTextBuilder {
+                text: slider.label(),
+                font_size: self.font_size,
+                color: self.font_color,
+                font: self.font,
+                password: false,
+                align: Some(Anchor::Middle),
+                line_mode: None,
+                editable: None,
+            },
+        };
+
+        let widget = UiWidget::Container {
+            background: Some(UiImageBuilder {
+                image: self.track_texture,
+            }),
+            transform: UiTransformBuilder {
+                mouse_reactive: true,
+                ..transform
+            },
+            children: vec![fill, thumb, label],
+        };
+
+        (widget, WidgetData::Slider(slider))
+    }
+}
+
+// the value of a slider widget, dragged with the left mouse button
+#[derive(Clone)]
+pub struct Slider {
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+    pub value: f32,
+    pub decimals: usize,
+    dragging: bool,
+}
+
+impl Component for Slider {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Slider {
+    pub fn fraction(&self) -> f32 {
+        if self.max > self.min {
+            ((self.value - self.min) / (self.max - self.min))
+                .max(0.0)
+                .min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn label(&self) -> String {
+        format!("{:.*}", self.decimals, self.value)
+    }
+
+    // returns true if the value changed
+    fn set_fraction(&mut self, fraction: f32) -> bool {
+        let mut value = self.min + (self.max - self.min) * fraction.max(0.0).min(1.0);
+        if self.step > 0.0 {
+            value = self.min + ((value - self.min) / self.step).round() * self.step;
+            value = value.min(self.max);
+        }
+
+        let changed = (value - self.value).abs() > std::f32::EPSILON;
+        self.value = value;
+        changed
+    }
+
+    // moves the value by a number of steps, or by tenths without a step.
+    // Returns true if the value changed
+    pub fn nudge(&mut self, steps: i32) -> bool {
+        let range = self.max - self.min;
+        let step = if self.step > 0.0 {
+            self.step
+        } else {
+            range / 10.0
+        };
+
+        if range > 0.0 {
+            self.set_fraction(self.fraction() + step * steps as f32 / range)
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SliderSystem {
+    event_reader: Option<ReaderId<UiEvent>>,
+}
+
+impl<'a> System<'a> for SliderSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EventChannel<UiEvent>>,
+        Write<'a, EventChannel<WidgetEvent>>,
+        Read<'a, InputHandler<String, String>>,
+        ReadExpect<'a, ParentHierarchy>,
+        WriteStorage<'a, Slider>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ui_events,
+            mut widget_events,
+            input,
+            hierarchy,
+            mut sliders,
+            mut transforms,
+            mut texts,
+        ): Self::SystemData,
+    ) {
+        for event in ui_events.read(self.event_reader.as_mut().unwrap()) {
+            if event.event_type == UiEventType::ClickStart {
+                if let Some(slider) = sliders.get_mut(event.target) {
+                    slider.dragging = true;
+                }
+            }
+        }
+
+        let held = input.mouse_button_is_down(MouseButton::Left);
+        let mouse_x = input.mouse_position().map(|(x, _)| x as f32);
+
+        for (entity, slider) in (&entities, &mut sliders).join() {
+            slider.dragging &= held;
+
+            let transform = match transforms.get(entity) {
+                Some(transform) => transform.clone(),
+                None => continue,
+            };
+            let (width, height) = (transform.width, transform.height);
+
+            if let (true, Some(mouse_x)) = (slider.dragging, mouse_x) {
+                let left = transform.pixel_x() - transform.pixel_width() / 2.0;
+                let fraction = (mouse_x - left) / transform.pixel_width();
+                if transform.pixel_width() > 0.0 && slider.set_fraction(fraction) {
+                    widget_events.single_write(WidgetEvent {
+                        target: entity,
+                        id: transform.id.clone(),
+                        value: WidgetValue::Number(slider.value),
+                    });
+                }
+            }
+
+            let fraction = slider.fraction();
+            let fill = widget::find_part(entity, "fill", &hierarchy, &transforms);
+            if let Some(fill) = fill.and_then(|fill| transforms.get_mut(fill)) {
+                fill.width = width * fraction;
+                fill.height = height;
+                fill.local_x = fill.width / 2.0;
+            }
+
+            let thumb = widget::find_part(entity, "thumb", &hierarchy, &transforms);
+            if let Some(thumb) = thumb.and_then(|thumb| transforms.get_mut(thumb)) {
+                thumb.local_x = width * fraction;
+            }
+
+            let label = widget::find_part(entity, "label", &hierarchy, &transforms);
+            if let Some(label) = label.and_then(|label| texts.get_mut(label)) {
+                label.text = slider.label();
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use super::{
+    widget::{self, WidgetData, WidgetEvent, WidgetValue},
+    CustomUi,
+};
+use amethyst::{
+    assets::AssetPrefab,
+    audio::AudioFormat,
+    core::{shrev::EventChannel, specs::prelude::*, transform::components::ParentHierarchy},
+    renderer::{TextureFormat, TexturePrefab},
+    ui::{
+        Anchor, FontAsset, FontFormat, TextEditingPrefab, UiImageBuilder, UiText, UiTextBuilder,
+        UiTransform, UiTransformBuilder, UiWidget,
+    },
+};
+use serde::Deserialize;
+
+const SPACING: f32 = 10.0;
+
+// what a text input accepts
+#[derive(Clone, Deserialize)]
+pub enum Validation {
+    Any,
+    NotEmpty,
+    Integer { min: i64, max: i64 },
+    Number { min: f32, max: f32 },
+}
+
+impl Default for Validation {
+    fn default() -> Self {
+        Validation::Any
+    }
+}
+
+impl Validation {
+    pub fn check(&self, text: &str) -> bool {
+        match *self {
+            Validation::Any => true,
+            Validation::NotEmpty => !text.trim().is_empty(),
+            Validation::Integer { min, max } => text
+                .trim()
+                .parse::<i64>()
+                .map_or(false, |value| value >= min && value <= max),
+            Validation::Number { min, max } => text
+                .trim()
+                .parse::<f32>()
+                .map_or(false, |value| value >= min && value <= max),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct UiTextInput {
+    pub value: String,
+    #[serde(default)]
+    pub validation: Validation,
+    pub max_length: usize,
+    pub background_texture: TexturePrefab<TextureFormat>,
+    pub font: Option<AssetPrefab<FontAsset, FontFormat>>,
+    pub font_color: [f32; 4],
+    // the text color while the text isn't valid
+    pub invalid_color: [f32; 4],
+    pub font_size: f32,
+}
+
+impl UiTextInput {
+    pub fn native_widget(
+        self,
+        transform: UiTransformBuilder,
+    ) -> (
+        UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>,
+        WidgetData,
+    ) {
+        let text = UiWidget::Text {
+            transform: UiTransformBuilder {
+                id: widget::part_id(&transform.id, "text"),
+                z: 1.0,
+                width: transform.width - SPACING * 2.0,
+                height: transform.height,
+                anchor: Anchor::Middle,
+                mouse_reactive: true,
+                ..Default::default()
+            },
+            text: UiTextBuilder {
+                text: self.value.clone(),
+                font_size: self.font_size,
+                color: self.font_color,
+                font: self.font,
+                password: false,
+                align: Some(Anchor::MiddleLeft),
+                line_mode: None,
+                editable: Some(TextEditingPrefab {
+                    max_length: self.max_length,
+                    selected_text_color: [0.0, 0.0, 0.0, 1.0],
+                    selected_background_color: self.font_color,
+                    use_block_cursor: false,
+                }),
+            },
+        };
+
+        let widget = UiWidget::Container {
+            background: Some(UiImageBuilder {
+                image: self.background_texture,
+            }),
+            transform,
+            children: vec![text],
+        };
+
+        let text_input = TextInput {
+            text: self.value.clone(),
+            value: self.value,
+            validation: self.validation,
+            valid: true,
+            font_color: self.font_color,
+            invalid_color: self.invalid_color,
+            dirty: false,
+        };
+
+        (widget, WidgetData::TextInput(text_input))
+    }
+}
+
+// The value of a text input, only updated with text that passed validation
+#[derive(Clone)]
+pub struct TextInput {
+    value: String,
+    // the text as last seen in the widget, can be invalid
+    text: String,
+    pub validation: Validation,
+    valid: bool,
+    font_color: [f32; 4],
+    invalid_color: [f32; 4],
+    dirty: bool,
+}
+
+impl Component for TextInput {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl TextInput {
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    // replaces the text shown in the widget
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.dirty = true;
+    }
+}
+
+#[derive(Default)]
+pub struct TextInputSystem;
+
+impl<'a> System<'a> for TextInputSystem {
+    type SystemData = (
+        Entities<'a>,
+        Write<'a, EventChannel<WidgetEvent>>,
+        ReadExpect<'a, ParentHierarchy>,
+        WriteStorage<'a, TextInput>,
+        ReadStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut widget_events,
+            hierarchy,
+            mut text_inputs,
+            transforms,
+            mut texts,
+        ): Self::SystemData,
+    ) {
+        for (entity, input) in (&entities, &mut text_inputs).join() {
+            let text = widget::find_part(entity, "text", &hierarchy, &transforms);
+            let text = match text.and_then(|text| texts.get_mut(text)) {
+                Some(text) => text,
+                None => continue,
+            };
+
+            if input.dirty {
+                input.dirty = false;
+                input.text = input.value.clone();
+                input.valid = true;
+                text.text = input.value.clone();
+            } else if text.text != input.text {
+                input.text = text.text.clone();
+                input.valid = input.validation.check(&input.text);
+
+                if input.valid && input.text != input.value {
+                    input.value = input.text.clone();
+                    widget_events.single_write(WidgetEvent {
+                        target: entity,
+                        id: transforms
+                            .get(entity)
+                            .map(|t| t.id.clone())
+                            .unwrap_or_default(),
+                        value: WidgetValue::Text(input.value.clone()),
+                    });
+                }
+            }
+
+            text.color = if input.valid {
+                input.font_color
+            } else {
+                input.invalid_color
+            };
+        }
+    }
+}
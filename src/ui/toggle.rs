@@ -0,0 +1,161 @@
+use super::{
+    widget::{self, WidgetData, WidgetEvent, WidgetValue},
+    CustomUi,
+};
+use amethyst::{
+    assets::AssetPrefab,
+    audio::AudioFormat,
+    core::{shrev::EventChannel, specs::prelude::*, transform::components::ParentHierarchy},
+    renderer::{Hidden, TextureFormat, TexturePrefab},
+    ui::{
+        Anchor, FontAsset, FontFormat, UiEvent, UiEventType, UiImageBuilder, UiTextBuilder,
+        UiTransform, UiTransformBuilder, UiWidget,
+    },
+};
+use serde::Deserialize;
+
+const SPACING: f32 = 15.0;
+
+#[derive(Clone, Deserialize)]
+pub struct UiToggle {
+    pub value: bool,
+    pub box_texture: TexturePrefab<TextureFormat>,
+    pub check_texture: TexturePrefab<TextureFormat>,
+    // shown right of the box
+    pub text: String,
+    pub text_width: f32,
+    pub font: Option<AssetPrefab<FontAsset, FontFormat>>,
+    pub font_color: [f32; 4],
+    pub font_size: f32,
+}
+
+impl UiToggle {
+    pub fn native_widget(
+        self,
+        transform: UiTransformBuilder,
+    ) -> (
+        UiWidget<AudioFormat, TextureFormat, FontFormat, CustomUi>,
+        WidgetData,
+    ) {
+        let check = UiWidget::Image {
+            transform: UiTransformBuilder {
+                id: widget::part_id(&transform.id, "check"),
+                z: 1.0,
+                width: transform.width * 0.6,
+                height: transform.height * 0.6,
+                anchor: Anchor::Middle,
+                ..Default::default()
+            },
+            image: UiImageBuilder {
+                image: self.check_texture,
+            },
+        };
+
+        let label = UiWidget::Text {
+            transform: UiTransformBuilder {
+                id: widget::part_id(&transform.id, "label"),
+                x: SPACING + self.text_width / 2.0,
+                width: self.text_width,
+                height: transform.height,
+                anchor: Anchor::MiddleRight,
+                ..Default::default()
+            },
+            text: UiTextBuilder {
+                text: self.text,
+                font_size: self.font_size,
+                color: self.font_color,
+                font: self.font,
+                password: false,
+                align: Some(Anchor::MiddleLeft),
+                line_mode: None,
+                editable: None,
+            },
+        };
+
+        let widget = UiWidget::Container {
+            background: Some(UiImageBuilder {
+                image: self.box_texture,
+            }),
+            transform: UiTransformBuilder {
+                mouse_reactive: true,
+                ..transform
+            },
+            children: vec![check, label],
+        };
+
+        (widget, WidgetData::Toggle(Toggle { value: self.value }))
+    }
+}
+
+// the value of a checkbox widget, flipped by clicking it
+#[derive(Clone)]
+pub struct Toggle {
+    pub value: bool,
+}
+
+impl Component for Toggle {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Default)]
+pub struct ToggleSystem {
+    event_reader: Option<ReaderId<UiEvent>>,
+}
+
+impl<'a> System<'a> for ToggleSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EventChannel<UiEvent>>,
+        Write<'a, EventChannel<WidgetEvent>>,
+        ReadExpect<'a, ParentHierarchy>,
+        WriteStorage<'a, Toggle>,
+        ReadStorage<'a, UiTransform>,
+        WriteStorage<'a, Hidden>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+    }
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ui_events,
+            mut widget_events,
+            hierarchy,
+            mut toggles,
+            transforms,
+            mut hidden,
+        ): Self::SystemData,
+    ) {
+        for event in ui_events.read(self.event_reader.as_mut().unwrap()) {
+            if event.event_type != UiEventType::ClickStop {
+                continue;
+            }
+
+            if let Some(toggle) = toggles.get_mut(event.target) {
+                toggle.value = !toggle.value;
+                widget_events.single_write(WidgetEvent {
+                    target: event.target,
+                    id: transforms
+                        .get(event.target)
+                        .map(|t| t.id.clone())
+                        .unwrap_or_default(),
+                    value: WidgetValue::Bool(toggle.value),
+                });
+            }
+        }
+
+        for (entity, toggle) in (&entities, &toggles).join() {
+            if let Some(check) = widget::find_part(entity, "check", &hierarchy, &transforms) {
+                if toggle.value {
+                    hidden.remove(check);
+                } else if !hidden.contains(check) {
+                    hidden.insert(check, Hidden).unwrap();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use super::{
+    cycle::{Cycle, CycleSystem},
+    layout::{Layout, LayoutSystem},
+    navigation::NavigationSystem,
+    progress_bar::{ProgressBar, ProgressBarSystem},
+    scroll_list::{ScrollList, ScrollListSystem},
+    slider::{Slider, SliderSystem},
+    text_input::{TextInput, TextInputSystem},
+    toggle::{Toggle, ToggleSystem},
+};
+use crate::profiler::Profiler;
+use amethyst::{
+    assets::{PrefabData, PrefabError},
+    core::{
+        bundle::{Result, SystemBundle},
+        specs::{prelude::*, storage::MaskedStorage},
+        transform::components::ParentHierarchy,
+    },
+    ui::UiTransform,
+};
+use std::ops::Deref;
+
+// the value a widget changed to
+#[derive(Clone, Debug, PartialEq)]
+pub enum WidgetValue {
+    Number(f32),
+    Bool(bool),
+    Index(usize),
+    Text(String),
+}
+
+// Sent through an EventChannel<WidgetEvent> whenever the user changes a widget.
+// Values set by code don't send events
+#[derive(Clone, Debug)]
+pub struct WidgetEvent {
+    pub target: Entity,
+    pub id: String,
+    pub value: WidgetValue,
+}
+
+// the value component a custom widget attaches to its root entity
+#[derive(Clone)]
+pub enum WidgetData {
+    None,
+    Layout(Layout),
+    ProgressBar(ProgressBar),
+    Slider(Slider),
+    Toggle(Toggle),
+    Cycle(Cycle),
+    TextInput(TextInput),
+    ScrollList(ScrollList),
+}
+
+impl Default for WidgetData {
+    fn default() -> Self {
+        WidgetData::None
+    }
+}
+
+impl<'a> PrefabData<'a> for WidgetData {
+    type SystemData = (
+        WriteStorage<'a, Layout>,
+        WriteStorage<'a, ProgressBar>,
+        WriteStorage<'a, Slider>,
+        WriteStorage<'a, Toggle>,
+        WriteStorage<'a, Cycle>,
+        WriteStorage<'a, TextInput>,
+        WriteStorage<'a, ScrollList>,
+    );
+    type Result = ();
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        (layouts, progress_bars, sliders, toggles, cycles, text_inputs, scroll_lists): &mut Self::SystemData,
+        _: &[Entity],
+    ) -> std::result::Result<(), PrefabError> {
+        match self {
+            WidgetData::None => (),
+            WidgetData::Layout(layout) => {
+                layouts.insert(entity, layout.clone())?;
+            }
+            WidgetData::ProgressBar(progress_bar) => {
+                progress_bars.insert(entity, progress_bar.clone())?;
+            }
+            WidgetData::Slider(slider) => {
+                sliders.insert(entity, slider.clone())?;
+            }
+            WidgetData::Toggle(toggle) => {
+                toggles.insert(entity, toggle.clone())?;
+            }
+            WidgetData::Cycle(cycle) => {
+                cycles.insert(entity, cycle.clone())?;
+            }
+            WidgetData::TextInput(text_input) => {
+                text_inputs.insert(entity, text_input.clone())?;
+            }
+            WidgetData::ScrollList(scroll_list) => {
+                scroll_lists.insert(entity, scroll_list.clone())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Widgets give their parts ids derived from their own id,
+// so the widget's id has to be unique
+pub fn part_id(id: &str, part: &str) -> String {
+    format!("{}_{}", id, part)
+}
+
+// finds a part of the widget by the id it was built with
+pub fn find_part<D>(
+    widget: Entity,
+    part: &str,
+    hierarchy: &ParentHierarchy,
+    transforms: &Storage<'_, UiTransform, D>,
+) -> Option<Entity>
+where
+    D: Deref<Target = MaskedStorage<UiTransform>>,
+{
+    let id = part_id(&transforms.get(widget)?.id, part);
+
+    hierarchy
+        .children(widget)
+        .iter()
+        .cloned()
+        .find(|&child| transforms.get(child).map_or(false, |t| t.id == id))
+}
+
+// the systems of the custom widgets and the menu navigation, timed by the profiler
+pub struct WidgetBundle {
+    profiler: Profiler,
+}
+
+impl WidgetBundle {
+    pub fn new(profiler: &Profiler) -> Self {
+        WidgetBundle {
+            profiler: profiler.clone(),
+        }
+    }
+
+    fn add<'a, 'b, S>(&self, builder: &mut DispatcherBuilder<'a, 'b>, system: S, name: &'static str)
+    where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        builder.add(self.profiler.timed(system, name), name, &[]);
+    }
+}
+
+impl<'a, 'b> SystemBundle<'a, 'b> for WidgetBundle {
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<()> {
+        self.add(builder, LayoutSystem, "layout");
+        self.add(builder, NavigationSystem::default(), "ui_navigation");
+        self.add(builder, ProgressBarSystem, "progress_bar");
+        self.add(builder, SliderSystem::default(), "slider");
+        self.add(builder, ToggleSystem::default(), "toggle");
+        self.add(builder, CycleSystem::default(), "cycle");
+        self.add(builder, TextInputSystem::default(), "text_input");
+        self.add(builder, ScrollListSystem::default(), "scroll_list");
+
+        Ok(())
+    }
+}